@@ -1,11 +1,176 @@
-use std::ffi::OsStr;
-use std::path::Component;
-use std::sync::LazyLock;
+use std::collections::BTreeMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
 
-static NODE_MODULES: LazyLock<Component> =
-	LazyLock::new(|| Component::Normal(OsStr::new("node_modules")));
-static PACKAGE_LOCK_JSON: LazyLock<&OsStr> = LazyLock::new(|| OsStr::new("package-lock.json"));
+use crate::options::Options;
 
-pub fn default_ignore_rule(path: &ignore::DirEntry) -> bool {
-	path.path().components().all(|c| c != *NODE_MODULES) && path.file_name() != *PACKAGE_LOCK_JSON
+/// Files and directories which are downloaded or generated by tools rather than written by
+/// hand, grouped by the ecosystem they come from. They're skipped unless `-A` is given. Names
+/// ending with a `/` only match directories, and everything else only matches files.
+pub const DEFAULT_IGNORES: &[(&str, &[&str])] = &[
+	(
+		"node",
+		&[
+			"node_modules/",
+			"bower_components/",
+			"dist/",
+			"package-lock.json",
+			"npm-shrinkwrap.json",
+			"yarn.lock",
+			"pnpm-lock.yaml",
+		],
+	),
+	("rust", &["target/", "Cargo.lock"]),
+	("go", &["vendor/", "go.sum"]),
+	("php", &["vendor/", "composer.lock"]),
+	("ruby", &[".bundle/", "Gemfile.lock"]),
+	(
+		"python",
+		&[
+			".venv/",
+			"venv/",
+			"__pycache__/",
+			".tox/",
+			".mypy_cache/",
+			"Pipfile.lock",
+			"poetry.lock",
+		],
+	),
+	(
+		"swift",
+		&["Pods/", "Carthage/", ".build/", "Package.resolved"],
+	),
+	("dart", &[".dart_tool/", "pubspec.lock"]),
+	("elixir", &["_build/", "deps/", "mix.lock"]),
+];
+
+/// Whether `--no-default-ignore` knows what `name` means, which is either an ecosystem, one
+/// of the names in it, or `all`
+pub fn is_default_ignore(name: &str) -> bool {
+	name == "all"
+		|| DEFAULT_IGNORES
+			.iter()
+			.any(|(ecosystem, names)| *ecosystem == name || names.contains(&name))
+}
+
+/// Everything that `--no-default-ignore` accepts, for suggestions
+pub fn default_ignore_names() -> Vec<&'static str> {
+	let mut names = vec!["all"];
+	for (ecosystem, entries) in DEFAULT_IGNORES {
+		names.push(ecosystem);
+		names.extend(entries.iter());
+	}
+	names
+}
+
+/// The names which are ignored by default with the given options, after removing the ones
+/// turned off by `--no-default-ignore`, and adding the ones from `--default-ignore`
+pub fn default_ignores(options: &Options) -> Vec<String> {
+	// Some names, like `vendor/`, belong to more than one ecosystem, and turning off any of
+	// them should be enough to stop ignoring it
+	let mut disabled = HashSet::new();
+	for name in &options.disabled_default_ignores {
+		match DEFAULT_IGNORES
+			.iter()
+			.find(|(ecosystem, _)| ecosystem == name)
+		{
+			Some((_, entries)) => disabled.extend(entries.iter().copied()),
+			None if name == "all" => disabled.extend(DEFAULT_IGNORES.iter().flat_map(|it| it.1)),
+			None => {
+				disabled.insert(name.as_str());
+			}
+		}
+	}
+
+	let mut names = Vec::<String>::new();
+	for name in DEFAULT_IGNORES
+		.iter()
+		.flat_map(|(_, entries)| entries.iter())
+	{
+		if !disabled.contains(name) && !names.iter().any(|it| it == name) {
+			names.push(name.to_string());
+		}
+	}
+	names.extend(options.extra_default_ignores.iter().cloned());
+	names
+}
+
+/// Finds which of the default ignores, if any, matches the last component of `path`
+pub fn default_ignore_match<'a>(names: &'a [String], path: &Path, is_dir: bool) -> Option<&'a str> {
+	let file_name = path.file_name()?.to_str()?;
+	names
+		.iter()
+		.find(|name| match name.strip_suffix('/') {
+			Some(dir_name) => is_dir && dir_name == file_name,
+			None => !is_dir && *name == file_name,
+		})
+		.map(String::as_str)
+}
+
+/// Builds a filter for the walker which skips the default ignores, and keeps count of which
+/// ones it skipped, for `--verbose`. The walker never filters the paths it was given, so
+/// something like `kc target/` still scans everything inside of `target/`.
+pub fn default_ignore_rule(
+	options: &Options,
+	skipped: Arc<Mutex<BTreeMap<String, usize>>>,
+) -> impl Fn(&ignore::DirEntry) -> bool + Send + Sync + 'static {
+	let names = default_ignores(options);
+	move |entry| {
+		let is_dir = entry.file_type().is_some_and(|it| it.is_dir());
+		match default_ignore_match(&names, entry.path(), is_dir) {
+			Some(name) => {
+				*skipped.lock().unwrap().entry(name.to_string()).or_default() += 1;
+				false
+			}
+			None => true,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn default_ignore_matches() {
+		let options = Options::default();
+		let names = default_ignores(&options);
+		let matches = |path: &str, is_dir| default_ignore_match(&names, Path::new(path), is_dir);
+
+		assert_eq!(matches("web/node_modules", true), Some("node_modules/"));
+		assert_eq!(matches("Cargo.lock", false), Some("Cargo.lock"));
+		assert_eq!(matches("src/target", true), Some("target/"));
+		assert_eq!(matches("src/target", false), None);
+		assert_eq!(matches("src/main.rs", false), None);
+
+		let options = Options {
+			disabled_default_ignores: vec!["rust".into(), "vendor/".into()],
+			extra_default_ignores: vec!["generated/".into()],
+			..Default::default()
+		};
+		let names = default_ignores(&options);
+		let matches = |path: &str, is_dir| default_ignore_match(&names, Path::new(path), is_dir);
+
+		assert_eq!(matches("Cargo.lock", false), None);
+		assert_eq!(matches("vendor", true), None);
+		assert_eq!(matches("go.sum", false), Some("go.sum"));
+		assert_eq!(matches("composer.lock", false), Some("composer.lock"));
+		assert_eq!(matches("generated", true), Some("generated/"));
+
+		let options = Options {
+			disabled_default_ignores: vec!["go".into()],
+			..Default::default()
+		};
+		let names = default_ignores(&options);
+		assert!(!names.iter().any(|it| it == "vendor/"));
+		assert!(names.iter().any(|it| it == "composer.lock"));
+
+		let options = Options {
+			disabled_default_ignores: vec!["all".into()],
+			..Default::default()
+		};
+		assert!(default_ignores(&options).is_empty());
+	}
 }
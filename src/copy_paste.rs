@@ -0,0 +1,261 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+use crate::fc::CodeLine;
+use crate::langs::Language;
+use crate::reporters::percent;
+
+/// A counted file's lines of code, for `--copy-paste`
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+	pub path: PathBuf,
+	pub language: Language,
+	pub lines: Vec<CodeLine>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CopyPasteSummary {
+	pub language: Language,
+	pub code_lines: usize,
+	/// Lines of code which are part of a block that already appeared somewhere else. The first
+	/// appearance of a block isn't counted, so that two identical files are 50% copied.
+	pub copied_lines: usize,
+}
+
+impl CopyPasteSummary {
+	pub fn percent(&self) -> f64 {
+		percent(self.copied_lines, self.code_lines)
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+	pub path: PathBuf,
+	pub start_line: usize,
+	pub end_line: usize,
+}
+
+/// A block of code which appears in two places
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fragment {
+	pub language: Language,
+	/// Lines of code, so not counting any blank lines or comments in between
+	pub code_lines: usize,
+	pub original: Location,
+	pub copy: Location,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CopyPaste {
+	/// With the most copied lines first
+	pub summaries: Vec<CopyPasteSummary>,
+	/// With the largest first
+	pub fragments: Vec<Fragment>,
+}
+
+/// Finds blocks of at least `min_lines` lines of code which appear more than once among files
+/// of the same language. Every window of `min_lines` lines is hashed, and windows which were
+/// already seen are matched up with their first appearance, and then matches which continue
+/// on from each other are joined together into larger fragments.
+pub fn find(files: &[SourceFile], min_lines: usize) -> CopyPaste {
+	let min_lines = min_lines.max(1);
+	let mut by_language = HashMap::<Language, Vec<&SourceFile>>::new();
+	for file in files {
+		by_language.entry(file.language).or_default().push(file);
+	}
+
+	let mut copy_paste = CopyPaste::default();
+	for (language, files) in by_language {
+		let mut first_seen = HashMap::<u64, (usize, usize)>::new();
+		// (original file, original line, copy file, copy line), as indexes into `lines`
+		let mut matches = Vec::<(usize, usize, usize, usize)>::new();
+		let mut copied = files
+			.iter()
+			.map(|file| vec![false; file.lines.len()])
+			.collect::<Vec<_>>();
+
+		for (f, file) in files.iter().enumerate() {
+			for (j, window) in file.lines.windows(min_lines).enumerate() {
+				let mut hasher = DefaultHasher::new();
+				for line in window {
+					line.hash.hash(&mut hasher);
+				}
+				match first_seen.entry(hasher.finish()) {
+					Entry::Vacant(entry) => {
+						entry.insert((f, j));
+					}
+					Entry::Occupied(entry) => {
+						let (original_f, i) = *entry.get();
+						// A long run of the same line would otherwise match itself
+						if original_f == f && j < i + min_lines {
+							continue;
+						}
+						matches.push((original_f, i, f, j));
+						copied[f][j..j + min_lines].fill(true);
+					}
+				}
+			}
+		}
+
+		copy_paste.summaries.push(CopyPasteSummary {
+			language,
+			code_lines: files.iter().map(|file| file.lines.len()).sum(),
+			copied_lines: copied.iter().flatten().filter(|it| **it).count(),
+		});
+
+		// Matches on the same diagonal which follow on from each other are one larger block
+		matches.sort_by_key(|&(original_f, i, f, j)| (original_f, f, j as isize - i as isize, i));
+		let mut start = 0;
+		while start < matches.len() {
+			let mut end = start;
+			while let Some(next) = matches.get(end + 1) {
+				let (original_f, i, f, j) = matches[end];
+				if *next != (original_f, i + 1, f, j + 1) {
+					break;
+				}
+				end += 1;
+			}
+
+			let (original_f, i, f, j) = matches[start];
+			let length = end - start + min_lines;
+			let location = |file: &SourceFile, first: usize| Location {
+				path: file.path.clone(),
+				start_line: file.lines[first].number,
+				end_line: file.lines[first + length - 1].number,
+			};
+			copy_paste.fragments.push(Fragment {
+				language,
+				code_lines: length,
+				original: location(files[original_f], i),
+				copy: location(files[f], j),
+			});
+			start = end + 1;
+		}
+	}
+
+	copy_paste
+		.summaries
+		.retain(|summary| summary.copied_lines > 0);
+	copy_paste.summaries.sort_by(|a, b| {
+		b.copied_lines
+			.cmp(&a.copied_lines)
+			.then_with(|| a.language.to_string().cmp(&b.language.to_string()))
+	});
+	copy_paste.fragments.sort_by(|a, b| {
+		b.code_lines
+			.cmp(&a.code_lines)
+			.then_with(|| a.original.path.cmp(&b.original.path))
+			.then(a.original.start_line.cmp(&b.original.start_line))
+			.then_with(|| a.copy.path.cmp(&b.copy.path))
+			.then(a.copy.start_line.cmp(&b.copy.start_line))
+	});
+	copy_paste
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::fc::code_lines;
+	use crate::langs::Language::*;
+
+	fn file(path: &str, language: Language, text: &str) -> SourceFile {
+		SourceFile {
+			path: path.into(),
+			language,
+			lines: code_lines(language, text),
+		}
+	}
+
+	#[test]
+	fn finds_copied_blocks() {
+		let block = "let a = 1;\nlet b = 2;\nlet c = 3;\n";
+		let files = [
+			file("a.rs", Rust, &format!("fn a() {{\n{block}}}\n")),
+			// Reindented, with a comment and a blank line in the middle, and an extra line
+			file(
+				"b.rs",
+				Rust,
+				"fn b() {\n\tlet a = 1;\n\n\t// two\n\tlet b = 2;\n\tlet c = 3;\n\tlet d = 4;\n}\n",
+			),
+			// A block can be copied within the same file
+			file("c.js", JavaScript, &format!("{block}{block}")),
+		];
+
+		let copy_paste = find(&files, 3);
+		assert_eq!(
+			copy_paste.fragments,
+			[
+				Fragment {
+					language: Rust,
+					code_lines: 3,
+					original: Location {
+						path: "a.rs".into(),
+						start_line: 2,
+						end_line: 4,
+					},
+					copy: Location {
+						path: "b.rs".into(),
+						start_line: 2,
+						end_line: 6,
+					},
+				},
+				Fragment {
+					language: JavaScript,
+					code_lines: 3,
+					original: Location {
+						path: "c.js".into(),
+						start_line: 1,
+						end_line: 3,
+					},
+					copy: Location {
+						path: "c.js".into(),
+						start_line: 4,
+						end_line: 6,
+					},
+				},
+			]
+		);
+		assert_eq!(
+			copy_paste.summaries,
+			[
+				CopyPasteSummary {
+					language: JavaScript,
+					code_lines: 6,
+					copied_lines: 3,
+				},
+				CopyPasteSummary {
+					language: Rust,
+					code_lines: 11,
+					copied_lines: 3,
+				},
+			]
+		);
+
+		// Blocks have to be long enough
+		assert!(find(&files, 4).fragments.is_empty());
+
+		// The same lines in another language don't count
+		let files = [file("a.rs", Rust, block), file("c.js", JavaScript, block)];
+		let copy_paste = find(&files, 3);
+		assert!(copy_paste.fragments.is_empty());
+		assert!(copy_paste.summaries.is_empty());
+	}
+
+	#[test]
+	fn joins_overlapping_windows() {
+		let text = (1..=10)
+			.map(|n| format!("call({n});\n"))
+			.collect::<String>();
+		let files = [file("a.rs", Rust, &text), file("b.rs", Rust, &text)];
+
+		let copy_paste = find(&files, 4);
+		assert_eq!(copy_paste.fragments.len(), 1);
+		assert_eq!(copy_paste.fragments[0].code_lines, 10);
+		assert_eq!(copy_paste.summaries[0].copied_lines, 10);
+		assert_eq!(copy_paste.summaries[0].percent(), 50.0);
+	}
+}
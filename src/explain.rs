@@ -0,0 +1,332 @@
+use anyhow::anyhow;
+use std::env;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::Match;
+
+use crate::config::default_ignore_match;
+use crate::config::default_ignores;
+use crate::fc::classify;
+use crate::fc::is_test_in;
+use crate::fc::FileContent;
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+use crate::scan::explicit_ignores;
+use crate::scan::reached;
+use crate::scan::Rules;
+use crate::scan::KC_IGNORE;
+
+/// Handles `kc explain`, which describes what a scan would do with each path given: which rule
+/// would skip it, or which language it would be counted as, and how many lines it has.
+///
+/// The path is treated as part of a scan of the current directory, or of the directory given
+/// with `--root`, and it accepts the same options as a scan, so that `-a`, `-A`, `-x`, and
+/// friends can be taken into account.
+pub fn explain(args: impl IntoIterator<Item = impl AsRef<str>>) -> anyhow::Result<()> {
+	let mut root = None;
+	let mut rest = Vec::new();
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		match arg.as_ref() {
+			"-root" | "--root" => {
+				let path = args
+					.next()
+					.ok_or_else(|| anyhow!("expected a directory to follow {} flag", arg.as_ref()))?;
+				root = Some(PathBuf::from(path.as_ref()));
+			}
+			arg => rest.push(arg.to_string()),
+		}
+	}
+
+	let options = Options::from(rest)?;
+	let mut f = io::stdout().lock();
+	for path in &options.roots {
+		explain_path(path, root.as_deref(), &options, &mut f)?;
+	}
+	Ok(())
+}
+
+fn explain_path(
+	path: &Path,
+	root: Option<&Path>,
+	options: &Options,
+	f: &mut dyn Write,
+) -> anyhow::Result<()> {
+	if !path.exists() {
+		return Err(anyhow!("{} does not exist", path.display()));
+	}
+
+	let cwd = env::current_dir()?.canonicalize()?;
+	let absolute = path.canonicalize()?;
+	let root = match root {
+		Some(root) => {
+			let root = root
+				.canonicalize()
+				.map_err(|err| anyhow!("unable to read {}: {}", root.display(), err))?;
+			if !absolute.starts_with(&root) {
+				return Err(anyhow!(
+					"{} is not inside of {}",
+					path.display(),
+					root.display()
+				));
+			}
+			root
+		}
+		// Anything outside of the current directory is treated as if its parent were scanned
+		None if absolute.starts_with(&cwd) => cwd.clone(),
+		None => absolute.parent().unwrap_or(&absolute).to_path_buf(),
+	};
+
+	writeln!(f, "{}", path.display())?;
+	if let Some(reason) = skip_reason(&absolute, &root, &cwd, options)? {
+		writeln!(f, "  skipped, because {reason}")?;
+		return Ok(());
+	}
+
+	if path.is_dir() {
+		writeln!(f, "  a directory, which would be scanned")?;
+		return Ok(());
+	}
+
+	let Some((language, rule)) = classify(path)? else {
+		writeln!(
+			f,
+			"  skipped, because its language couldn't be determined from its file name, extension, or shebang"
+		)?;
+		return Ok(());
+	};
+	writeln!(f, "  recognized as {language} by {rule}")?;
+
+	// Judged by the path inside of the root, the same as a scan
+	let is_test = is_test_in(&absolute, &[&root]);
+	let category = language.info().category;
+	if options.excluded.contains(&language) {
+		writeln!(f, "  skipped, because {language} is excluded by --exclude")?;
+	} else if !options.only_include.is_empty() && !options.only_include.contains(&language) {
+		writeln!(f, "  skipped, because {language} isn't included by --only")?;
+	} else if !options.categories.is_empty()
+		&& !category.is_some_and(|it| options.categories.contains(&it))
+	{
+		let category = category.map(|it| it.to_string()).unwrap_or_default();
+		writeln!(
+			f,
+			"  skipped, because {category} isn't one of the categories chosen by --category"
+		)?;
+	} else if options.no_tests && is_test {
+		writeln!(
+			f,
+			"  skipped, because it's a test, and tests are excluded by --no-tests"
+		)?;
+	} else {
+		writeln!(f, "  counted")?;
+	}
+
+	let content = FileContent::new(path.to_path_buf(), is_test)?;
+	let mut summary = LanguageSummary::from(language);
+	summary.add(path.to_path_buf(), &content);
+	write!(
+		f,
+		"  {} {}: {} code, {} {}, {} blank",
+		summary.lines,
+		if summary.lines == 1 { "line" } else { "lines" },
+		summary.code_lines(),
+		summary.comment_lines,
+		if summary.comment_lines == 1 {
+			"comment"
+		} else {
+			"comments"
+		},
+		summary.blank_lines,
+	)?;
+	if content.test_lines > 0 && content.test_lines == content.lines {
+		writeln!(f, ", all of them tests")?;
+	} else if content.test_lines > 0 {
+		let excluded = if options.no_tests {
+			", which --no-tests excludes"
+		} else {
+			""
+		};
+		writeln!(f, ", {} of them in tests{}", content.test_lines, excluded)?;
+	} else {
+		writeln!(f)?;
+	}
+
+	Ok(())
+}
+
+/// Asks the walker whether it would reach the path when scanning `root`, and if it wouldn't,
+/// finds the first directory leading up to it (or the path itself) which it skips, and then
+/// which rule skips it, by turning the rules off one at a time until the walker reaches it.
+/// Paths given to a scan directly are never skipped, so neither is `root`.
+fn skip_reason(
+	absolute: &Path,
+	root: &Path,
+	cwd: &Path,
+	options: &Options,
+) -> anyhow::Result<Option<String>> {
+	let display = |path: &Path| {
+		path
+			.strip_prefix(cwd)
+			.map(|it| it.display().to_string())
+			.unwrap_or_else(|_| path.display().to_string())
+	};
+
+	let on_the_way = reached(options, Rules::ALL, root, absolute)?;
+	let Some(entry) = absolute
+		.ancestors()
+		.take_while(|it| *it != root)
+		.collect::<Vec<_>>()
+		.into_iter()
+		.rev()
+		.find(|it| !on_the_way.contains(*it))
+	else {
+		return Ok(None);
+	};
+	let is_dir = entry.is_dir();
+
+	// The walker checks --include and --exclude-path first, then ignore files, then whether the
+	// path is hidden, and then everything else, so the rules are turned off in the opposite
+	// order, and the one which lets the walker through is the first one that it would've hit.
+	let mut rules = Rules::ALL;
+	for rule in [
+		Rule::MaxFilesize,
+		Rule::DefaultIgnores,
+		Rule::OneFileSystem,
+		Rule::MaxDepth,
+		Rule::Hidden,
+		Rule::IgnoreFiles,
+		Rule::PathGlobs,
+	] {
+		rule.turn_off(&mut rules);
+		if !reached(options, rules, root, entry)?.contains(entry) {
+			continue;
+		}
+
+		let reason = match rule {
+			Rule::PathGlobs => format!(
+				"{} is filtered out by --include or --exclude-path",
+				display(entry)
+			),
+			Rule::IgnoreFiles => match ignoring_glob(entry, is_dir, options)? {
+				Some((glob, Some(from))) => format!(
+					"\"{}\" in {} ignores {}",
+					glob,
+					display(&from),
+					display(entry)
+				),
+				_ => format!("an ignore file ignores {}", display(entry)),
+			},
+			Rule::Hidden => format!(
+				"{} is hidden (use -a to include hidden files)",
+				display(entry)
+			),
+			Rule::MaxDepth => format!("{} is deeper than --max-depth", display(entry)),
+			Rule::OneFileSystem => format!(
+				"{} is on another file system, and --one-file-system is set",
+				display(entry)
+			),
+			Rule::DefaultIgnores => {
+				let names = default_ignores(options);
+				let name = default_ignore_match(&names, entry, is_dir).unwrap_or_default();
+				format!(
+					"{} matches \"{}\", which is ignored by default (use -A or --no-default-ignore to include it)",
+					display(entry),
+					name
+				)
+			}
+			Rule::MaxFilesize => format!(
+				"{} is larger than --max-filesize ({} bytes)",
+				display(entry),
+				options.max_filesize.unwrap_or_default()
+			),
+		};
+		return Ok(Some(reason));
+	}
+
+	Ok(Some(format!("{} couldn't be read", display(entry))))
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Rule {
+	PathGlobs,
+	IgnoreFiles,
+	Hidden,
+	MaxDepth,
+	OneFileSystem,
+	DefaultIgnores,
+	MaxFilesize,
+}
+
+impl Rule {
+	fn turn_off(self, rules: &mut Rules) {
+		match self {
+			Rule::PathGlobs => rules.path_globs = false,
+			Rule::IgnoreFiles => rules.ignore_files = false,
+			Rule::Hidden => rules.hidden = false,
+			Rule::MaxDepth => rules.max_depth = false,
+			Rule::OneFileSystem => rules.one_file_system = false,
+			Rule::DefaultIgnores => rules.default_ignores = false,
+			Rule::MaxFilesize => rules.max_filesize = false,
+		}
+	}
+}
+
+/// Finds the pattern that ignores a path, by going through the ignore files which the walker
+/// reads, in the same order that it does: each kind of file from the closest directory outwards,
+/// then the global gitignore, and then the files from --ignore-file.
+fn ignoring_glob(
+	entry: &Path,
+	is_dir: bool,
+	options: &Options,
+) -> anyhow::Result<Option<(String, Option<PathBuf>)>> {
+	let dirs = entry
+		.parent()
+		.map(|it| it.ancestors().collect::<Vec<_>>())
+		.unwrap_or_default();
+	let repo = dirs.iter().position(|dir| dir.join(".git").exists());
+
+	let mut ignores = Vec::new();
+	for name in [KC_IGNORE, ".ignore"] {
+		ignores.extend(dirs.iter().map(|dir| ignore_file(dir, &dir.join(name))));
+	}
+	if let Some(repo) = repo {
+		ignores.extend(
+			dirs[..=repo]
+				.iter()
+				.map(|dir| ignore_file(dir, &dir.join(".gitignore"))),
+		);
+		let top = dirs[repo];
+		ignores.push(ignore_file(top, &top.join(".git/info/exclude")));
+		ignores.push(Some(Gitignore::global().0));
+	}
+	if !options.ignore_files.is_empty() {
+		ignores.push(Some(explicit_ignores(options)?));
+	}
+
+	let matched = ignores
+		.iter()
+		.flatten()
+		.map(|ignore| ignore.matched(entry, is_dir))
+		.find(|it| !it.is_none());
+	Ok(match matched {
+		Some(Match::Ignore(glob)) => Some((
+			glob.original().to_string(),
+			glob.from().map(Path::to_path_buf),
+		)),
+		_ => None,
+	})
+}
+
+fn ignore_file(dir: &Path, file: &Path) -> Option<Gitignore> {
+	if !file.is_file() {
+		return None;
+	}
+	let mut builder = GitignoreBuilder::new(dir);
+	builder.add(file);
+	builder.build().ok()
+}
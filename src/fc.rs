@@ -1,42 +1,488 @@
-use std::fs::read_to_string;
+use std::fs;
+use std::fs::File;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
 use std::io::ErrorKind::*;
+use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
 
+use crate::langs::CommentSyntax;
 use crate::langs::Language;
+use crate::langs::Rule;
 
 #[derive(Clone, Debug)]
 pub struct FileContent {
 	pub language: Language,
 	pub lines: usize,
 	pub blank_lines: usize,
+	pub comment_lines: usize,
+	/// Lines which belong to tests, either because the whole file is a test, or because
+	/// they're inside of a test module, like Rust's `#[cfg(test)] mod tests`
+	pub test_lines: usize,
 }
 
 impl FileContent {
-	pub fn new(path: PathBuf) -> Result<Self, io::Error> {
-		let file_name = path
-			.file_name()
-			.ok_or_else(|| io::Error::new(NotFound, "file must have a file name"))?;
-		let language = Language::from_file_name(file_name).ok_or(io::Error::new(
+	/// Counts a file, which is entirely a test if `is_test` is set. Whether it's a test depends
+	/// on which root it was found in, which only the caller knows, so see `is_test_in`.
+	pub fn new(path: PathBuf, is_test: bool) -> Result<Self, io::Error> {
+		let (language, _) = classify(&path)?.ok_or(io::Error::new(
 			InvalidInput,
 			format!("unable to determine language for {path:?}",),
 		))?;
 
-		let text = read_to_string(&path)?;
+		let text = read_text(&path)?;
+		Ok(Self::from_file(language, &text, is_test, true))
+	}
+
+	/// Counts a file which is entirely a test if `is_test` is set. Otherwise, any test modules
+	/// inside of it are counted as tests, or left out completely without `include_tests`.
+	pub fn from_file(language: Language, text: &str, is_test: bool, include_tests: bool) -> Self {
+		if is_test {
+			let mut content = Self::count(language, text, true);
+			content.test_lines = content.lines;
+			content
+		} else {
+			Self::count(language, text, include_tests)
+		}
+	}
+
+	fn count(language: Language, text: &str, include_tests: bool) -> Self {
+		let syntax = language.comment_syntax();
+		let tests = match language {
+			Language::Rust => rust_test_lines(text),
+			_ => vec![],
+		};
 		let mut lines = 0;
 		let mut blank_lines = 0;
+		let mut comment_lines = 0;
+		let mut test_lines = 0;
+		// The end marker of the block comment we're currently inside of, if any
+		let mut block_end = None;
+
+		for (i, line) in text.lines().enumerate() {
+			if tests.get(i).copied().unwrap_or(false) {
+				if !include_tests {
+					continue;
+				}
+				test_lines += 1;
+			}
 
-		for line in text.lines() {
+			lines += 1;
 			if line.is_empty() {
 				blank_lines += 1;
+				continue;
+			}
+
+			// Lines with only whitespace aren't blank, but they are part of a comment if they're
+			// inside of a block comment.
+			let trimmed = line.trim();
+			let comment = if trimmed.is_empty() {
+				block_end.is_some()
+			} else {
+				is_comment(trimmed, &syntax, &mut block_end)
+			};
+			if comment {
+				comment_lines += 1;
 			}
-			lines += 1;
 		}
 
-		Ok(Self {
+		Self {
 			language,
 			lines,
 			blank_lines,
+			comment_lines,
+			test_lines,
+		}
+	}
+}
+
+/// A line of code with its whitespace taken out, so that code which was copied and then
+/// reindented still matches, for finding copy-pasted blocks
+#[derive(Clone, Debug)]
+pub struct CodeLine {
+	/// Starting from 1
+	pub number: usize,
+	pub hash: u64,
+}
+
+/// Every line of a file which isn't blank or only a comment
+pub fn code_lines(language: Language, text: &str) -> Vec<CodeLine> {
+	let syntax = language.comment_syntax();
+	let mut block_end = None;
+	let mut code = Vec::new();
+	for (i, line) in text.lines().enumerate() {
+		let trimmed = line.trim();
+		if trimmed.is_empty() || is_comment(trimmed, &syntax, &mut block_end) {
+			continue;
+		}
+
+		let mut hasher = DefaultHasher::new();
+		for c in trimmed.chars().filter(|c| !c.is_whitespace()) {
+			c.hash(&mut hasher);
+		}
+		code.push(CodeLine {
+			number: i + 1,
+			hash: hasher.finish(),
+		});
+	}
+	code
+}
+
+/// Like `is_test_path`, but only looking at the part of the path inside of whichever root it
+/// was found in, so that scanning a project which happens to live inside of a `tests`
+/// directory still works. A file which was given as a root itself is judged by its name.
+pub fn is_test_in(path: &Path, roots: &[impl AsRef<Path>]) -> bool {
+	let relative = match roots.iter().find_map(|root| path.strip_prefix(root).ok()) {
+		Some(relative) if relative.as_os_str().is_empty() => {
+			path.file_name().map(Path::new).unwrap_or(path)
+		}
+		Some(relative) => relative,
+		None => path,
+	};
+	is_test_path(relative)
+}
+
+/// Decides whether a file contains tests from its path, using the conventions of common test
+/// frameworks, like a `tests/` or `__tests__/` directory, `*_test.go`, `*.spec.ts` or
+/// `test_*.py`.
+pub fn is_test_path(path: &Path) -> bool {
+	let in_test_dir = path.parent().is_some_and(|parent| {
+		parent.components().any(|component| {
+			matches!(
+				component.as_os_str().to_str(),
+				Some("test" | "tests" | "__tests__" | "spec")
+			)
 		})
+	});
+	if in_test_dir {
+		return true;
+	}
+
+	let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+		return false;
+	};
+	// Only the last extension, so that the `.spec` in `thing.spec.ts` is kept
+	let stem = name.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(name);
+	stem.starts_with("test_")
+		|| [".test", "_test", ".spec", "_spec"]
+			.iter()
+			.any(|suffix| stem.ends_with(suffix))
+		// `ThingTest.java` or `ThingTests.cs`, but not `latest.rs`
+		|| ["Test", "Tests"]
+			.iter()
+			.any(|suffix| stem.len() > suffix.len() && stem.ends_with(suffix))
+}
+
+/// Marks which lines are part of a `#[cfg(test)]` module, including the attribute. The end of
+/// the module is found by counting braces, so a brace inside of a string literal can
+/// occasionally throw it off.
+fn rust_test_lines(text: &str) -> Vec<bool> {
+	let mut marks = Vec::new();
+	// Where the attribute was, if we're still looking for the module it belongs to
+	let mut attribute = None;
+	// How many braces are open inside of the test module we're in, if any
+	let mut depth = None;
+
+	for line in text.lines() {
+		let trimmed = line.trim();
+		if let Some(open) = depth {
+			marks.push(true);
+			let open =
+				open + trimmed.matches('{').count() as isize - trimmed.matches('}').count() as isize;
+			depth = (open > 0).then_some(open);
+			continue;
+		}
+
+		if trimmed == "#[cfg(test)]" {
+			attribute = Some(marks.len());
+		} else if let Some(start) = attribute {
+			let is_module = trimmed.starts_with("mod ") || trimmed.starts_with("pub mod ");
+			if is_module && trimmed.ends_with('{') {
+				marks[start..].fill(true);
+				marks.push(true);
+				depth = Some(1);
+				attribute = None;
+				continue;
+			}
+			// Other attributes can come between `#[cfg(test)]` and the module
+			if !trimmed.starts_with("#[") {
+				attribute = None;
+			}
+		}
+		marks.push(false);
+	}
+
+	marks
+}
+
+/// Reads a file as text. Bytes which aren't valid UTF-8, like from a file saved as Latin-1, are
+/// replaced rather than failing the whole file, since they don't change where its lines are.
+pub fn read_text(path: &Path) -> Result<String, io::Error> {
+	let bytes = fs::read(path)?;
+	Ok(match String::from_utf8(bytes) {
+		Ok(text) => text,
+		Err(err) => String::from_utf8_lossy(err.as_bytes()).into_owned(),
+	})
+}
+
+/// Decides which language a file is written in, and which rule decided it. Files without an
+/// extension also get checked for a shebang line, since scripts often don't have one.
+pub fn classify(path: &Path) -> Result<Option<(Language, Rule)>, io::Error> {
+	let file_name = path
+		.file_name()
+		.ok_or_else(|| io::Error::new(NotFound, "file must have a file name"))?;
+	if let Some(detected) = Language::detect(file_name) {
+		return Ok(Some(detected));
+	}
+	if path.extension().is_some() {
+		return Ok(None);
+	}
+
+	// Only read the beginning of the file, in case it's something large with no newlines
+	let mut first_line = String::new();
+	if BufReader::new(File::open(path)?.take(256))
+		.read_line(&mut first_line)
+		.is_err()
+	{
+		// Not text, so certainly not a script
+		return Ok(None);
+	}
+	Ok(
+		Language::from_shebang(&first_line)
+			.map(|(language, program)| (language, Rule::Shebang(program))),
+	)
+}
+
+/// Decides whether a (trimmed, non-empty) line contains only comments, and keeps track of
+/// whether the line leaves a block comment open. Comment markers inside of double quoted
+/// strings are skipped over, but strings which span several lines aren't understood, so those
+/// can occasionally throw it off.
+fn is_comment(line: &str, syntax: &CommentSyntax, block_end: &mut Option<&'static str>) -> bool {
+	let mut rest = line;
+	let mut only_comments = true;
+
+	loop {
+		if let Some(end) = *block_end {
+			match rest.find(end) {
+				Some(i) => {
+					rest = rest[i + end.len()..].trim_start();
+					*block_end = None;
+				}
+				None => return only_comments,
+			}
+		}
+
+		if rest.is_empty() {
+			return only_comments;
+		}
+
+		if let Some((start, end)) = syntax
+			.block
+			.iter()
+			.find(|(start, _)| rest.starts_with(start))
+		{
+			rest = &rest[start.len()..];
+			*block_end = Some(end);
+			continue;
+		}
+
+		if syntax.line.iter().any(|marker| rest.starts_with(marker)) {
+			return only_comments;
+		}
+
+		// There's code on this line, but it might still open a block comment after the code.
+		only_comments = false;
+		match find_block_start(rest, syntax) {
+			Some((i, start, end)) => {
+				rest = &rest[i + start.len()..];
+				*block_end = Some(end);
+			}
+			None => return false,
+		}
+	}
+}
+
+/// Finds where the first block comment starts in a line of code, skipping over anything in
+/// double quotes, and giving up at a line comment, since nothing after one of those counts
+fn find_block_start(
+	code: &str,
+	syntax: &CommentSyntax,
+) -> Option<(usize, &'static str, &'static str)> {
+	let mut in_string = false;
+	let mut chars = code.char_indices();
+	while let Some((i, c)) = chars.next() {
+		let rest = &code[i..];
+		if in_string {
+			match c {
+				'\\' => {
+					chars.next();
+				}
+				'"' => in_string = false,
+				_ => {}
+			}
+			continue;
+		}
+
+		// A quote as a character literal doesn't start a string
+		if rest.starts_with("'\"'") {
+			chars.nth(1);
+			continue;
+		}
+		if c == '"' {
+			in_string = true;
+			continue;
+		}
+		// Block markers are checked first, since some of them start with a line marker, like
+		// Lua's `--[[`
+		if let Some((start, end)) = syntax
+			.block
+			.iter()
+			.find(|(start, _)| rest.starts_with(start))
+		{
+			return Some((i, start, end));
+		}
+		if syntax.line.iter().any(|marker| rest.starts_with(marker)) {
+			return None;
+		}
+	}
+	None
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use Language::*;
+
+	fn count(language: Language, text: &str) -> (usize, usize, usize) {
+		let content = FileContent::from_file(language, text, false, true);
+		(content.lines, content.blank_lines, content.comment_lines)
+	}
+
+	#[test]
+	fn counts_comments() {
+		let rust = "\
+			// line comment\n\
+			fn main() { // trailing comment\n\
+			\n\
+			\t/* block\n\
+			\t   comment */\n\
+			\tlet x = 1; /* opens a block\n\
+			\tstill inside */ let y = 2;\n\
+			\t/* one */ /* two */\n\
+			}\n";
+		assert_eq!(count(Rust, rust), (9, 1, 4));
+
+		let python = "#!/usr/bin/env python3\n\n# comment\nprint('# not a comment')\n";
+		assert_eq!(count(Python, python), (4, 1, 2));
+
+		let lua = "--[[\nblock\n]]\n-- line\nprint(1)\n";
+		assert_eq!(count(Lua, lua), (5, 0, 4));
+
+		let json = "{\n\t\"a\": 1\n}\n";
+		assert_eq!(count(Json, json), (3, 0, 0));
+
+		// Markers inside of strings, or after a line comment, don't open a block comment
+		let rust = "\
+			let glob = \"src/*.rs\";\n\
+			let quote = '\"'; let url = \"http://a/*\";\n\
+			let x = 1; // see /* this\n\
+			let y = 2;\n\
+			let s = \"\\\"/*\"; /* but this one\n\
+			does */\n";
+		assert_eq!(count(Rust, rust), (6, 0, 1));
+	}
+
+	#[test]
+	fn code_lines_ignore_whitespace() {
+		let code = |text: &str| {
+			code_lines(Rust, text)
+				.into_iter()
+				.map(|line| (line.number, line.hash))
+				.collect::<Vec<_>>()
+		};
+
+		let original = code("fn main() {\n\t// hi\n\tlet x = 1;\n}\n");
+		let reindented = code("\n  fn main(){\n    let x=1;\n  }\n");
+		assert_eq!(
+			original.iter().map(|it| it.0).collect::<Vec<_>>(),
+			[1, 3, 4]
+		);
+		assert_eq!(
+			reindented.iter().map(|it| it.0).collect::<Vec<_>>(),
+			[2, 3, 4]
+		);
+		assert_eq!(
+			original.iter().map(|it| it.1).collect::<Vec<_>>(),
+			reindented.iter().map(|it| it.1).collect::<Vec<_>>(),
+		);
+	}
+
+	#[test]
+	fn test_paths() {
+		let is_test = |path: &str| is_test_path(Path::new(path));
+
+		assert!(is_test("tests/main.rs"));
+		assert!(is_test("web/src/__tests__/app.js"));
+		assert!(is_test("server/handler_test.go"));
+		assert!(is_test("src/app.spec.ts"));
+		assert!(is_test("src/Button.test.tsx"));
+		assert!(is_test("test_parser.py"));
+		assert!(is_test("src/main/java/ParserTest.java"));
+
+		assert!(!is_test("src/main.rs"));
+		assert!(!is_test("src/latest.rs"));
+		assert!(!is_test("tests"));
+		assert!(!is_test("contest.py"));
+		assert!(!is_test("src/testing.rs"));
+
+		// Only the part of the path inside of the root counts
+		let roots = [Path::new("/home/me/tests/proj")];
+		assert!(!is_test_in(
+			Path::new("/home/me/tests/proj/src/main.rs"),
+			&roots
+		));
+		assert!(is_test_in(
+			Path::new("/home/me/tests/proj/tests/main.rs"),
+			&roots
+		));
+		assert!(is_test_in(Path::new("/home/me/tests/main.rs"), &roots));
+		let roots = [Path::new("tests/proj/parser_test.go")];
+		assert!(is_test_in(Path::new("tests/proj/parser_test.go"), &roots));
+	}
+
+	#[test]
+	fn rust_test_modules() {
+		let rust = "\
+			fn main() {}\n\
+			\n\
+			#[cfg(test)]\n\
+			#[allow(unused)]\n\
+			mod tests {\n\
+			\tuse super::*;\n\
+			\n\
+			\t#[test]\n\
+			\tfn works() {\n\
+			\t\tmain();\n\
+			\t}\n\
+			}\n\
+			\n\
+			#[cfg(test)]\n\
+			mod other;\n";
+		let content = FileContent::from_file(Rust, rust, false, true);
+		assert_eq!((content.lines, content.test_lines), (15, 10));
+
+		let content = FileContent::from_file(Rust, rust, false, false);
+		assert_eq!(
+			(content.lines, content.blank_lines, content.test_lines),
+			(5, 2, 0)
+		);
+
+		let content = FileContent::from_file(Rust, rust, true, true);
+		assert_eq!((content.lines, content.test_lines), (15, 15));
 	}
 }
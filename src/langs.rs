@@ -3,11 +3,28 @@ use std::fmt;
 use std::fmt::Display;
 use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use crate::color::Color;
+use crate::fc::FileContent;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum Language {
+/// Declares the `Language` enum along with `Language::ALL`, so that a language can't be added
+/// to one without the other
+macro_rules! languages {
+	( $($language:ident,)* ) => {
+		#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+		pub enum Language {
+			$($language,)*
+		}
+
+		impl Language {
+			/// Every language that a file can be recognized as
+			pub const ALL: &'static [Language] = &[$(Language::$language,)*];
+		}
+	};
+}
+
+languages! {
 	Ada,
 	Assembly,
 	Astro,
@@ -131,311 +148,491 @@ impl Language {
 	where
 		S: AsRef<str>,
 	{
-		use Language::*;
-
-		match name.as_ref().to_ascii_lowercase().as_ref() {
-			"ada" => Some(Ada),
-			"assembly" => Some(Assembly),
-			"astro" => Some(Astro),
-			"bash" => Some(Bash),
-			"batch" => Some(Batch),
-			"bqn" => Some(Bqn),
-			"brainfuck" => Some(Brainfuck),
-			"c" => Some(C),
-			"carbon" => Some(Carbon),
-			"clojure" => Some(Clojure),
-			"cmake" => Some(CMake),
-			"cobol" => Some(Cobol),
-			"coffeescript" => Some(CoffeeScript),
-			"commonlisp" => Some(CommonLisp),
-			"crystal" => Some(Crystal),
-			"csharp" => Some(CSharp),
-			"c3" => Some(C3),
-			"c#" => Some(CSharp),
-			"css" => Some(Css),
-			"cue" => Some(Cue),
-			"c++" => Some(Cxx),
-			"d" => Some(D),
-			"dart" => Some(Dart),
-			"dhall" => Some(Dhall),
-			"dockerfile" => Some(Dockerfile),
-			"elm" => Some(Elm),
-			"erlang" => Some(Erlang),
-			"elixir" => Some(Elixir),
-			"fortran" => Some(Fortran),
-			"fish" => Some(Fish),
-			"fsharp" => Some(FSharp),
-			"f#" => Some(FSharp),
-			"gleam" => Some(Gleam),
-			"gn" => Some(Gn),
-			"go" => Some(Go),
-			"graphql" => Some(GraphQl),
-			"grain" => Some(Grain),
-			"gren" => Some(Gren),
-			"hare" => Some(Hare),
-			"haskell" => Some(Haskell),
-			"haxe" => Some(Haxe),
-			"html" => Some(Html),
-			"hylo" => Some(Hylo),
-			"idris" => Some(Idris),
-			"io" => Some(Io),
-			"jai" => Some(Jai),
-			"java" => Some(Java),
-			"julia" => Some(Julia),
-			"javascript" => Some(JavaScript),
-			"json" => Some(Json),
-			"koka" => Some(Koka),
-			"kotlin" => Some(Kotlin),
-			"llvm" => Some(Llvm),
-			"lua" => Some(Lua),
-			"objectivec" => Some(ObjectiveC),
-			"objective-c" => Some(ObjectiveC),
-			"markdown" => Some(Markdown),
-			"make" => Some(Make),
-			"metal" => Some(Metal),
-			"ocaml" => Some(OCaml),
-			"objectivec++" => Some(ObjectiveCxx),
-			"objective-c++" => Some(ObjectiveCxx),
-			"nim" => Some(Nim),
-			"nix" => Some(Nix),
-			"nushell" => Some(NuShell),
-			"odin" => Some(Odin),
-			"pascal" => Some(Pascal),
-			"php" => Some(Php),
-			"perl" => Some(Perl),
-			"prolog" => Some(Prolog),
-			"powershell" => Some(PowerShell),
-			"purescript" => Some(PureScript),
-			"python" => Some(Python),
-			"r" => Some(R),
-			"racket" => Some(Racket),
-			"raku" => Some(Raku),
-			"reason" => Some(Reason),
-			"reasonml" => Some(Reason),
-			"ren" => Some(Ren),
-			"rescript" => Some(ReScript),
-			"roc" => Some(Roc),
-			"ruby" => Some(Ruby),
-			"rust" => Some(Rust),
-			"sass" => Some(Sass),
-			"scss" => Some(Scss),
-			"scala" => Some(Scala),
-			"scmeme" => Some(Scheme),
-			"sf" => Some(Seafoam),
-			"sql" => Some(Sql),
-			"svelte" => Some(Svelte),
-			"swift" => Some(Swift),
-			"tcl" => Some(Tcl),
-			"terraform" => Some(Terraform),
-			"toml" => Some(Toml),
-			"typescript" => Some(TypeScript),
-			"unison" => Some(Unison),
-			"v" => Some(V),
-			"vala" => Some(Vala),
-			"vale" => Some(Vale),
-			"visualbasic" => Some(VisualBasic),
-			"vue" => Some(Vue),
-			"webassembly" => Some(WebAssembly),
-			"wren" => Some(Wren),
-			"xml" => Some(Xml),
-			"yall" => Some(Yall),
-			"yaml" => Some(Yaml),
-			"yuescript" => Some(YueScript),
-			"zig" => Some(Zig),
-			_ => None,
-		}
+		let name = name.as_ref().to_ascii_lowercase();
+		lookup(NAMES, &name)
 	}
 
-	pub fn from_file_name<S>(file_name: S) -> Option<Self>
+	/// Recognizes a language from a file's name or extension, and says which rule matched
+	pub fn detect<S>(file_name: S) -> Option<(Self, Rule)>
 	where
 		S: AsRef<OsStr>,
 	{
-		use Language::*;
+		if let Some((name, language)) = find(FILE_NAMES, file_name.as_ref().to_str()?) {
+			return Some((*language, Rule::FileName(name)));
+		}
+
+		let ext = Path::new(file_name.as_ref())
+			.extension()?
+			.to_str()?
+			.to_ascii_lowercase();
+		find(EXTENSIONS, &ext).map(|(ext, language)| (*language, Rule::Extension(ext)))
+	}
 
-		match file_name.as_ref().to_str()? {
-			"Cakefile" => Some(CoffeeScript),
-			"CMakeLists.txt" => Some(CMake),
-			"Dockerfile" => Some(Dockerfile),
-			"Makefile" => Some(Make),
-			"Rakefile" => Some(Ruby),
-			_ => Path::new(file_name.as_ref())
-				.extension()
-				.and_then(Language::from_extension),
+	/// Recognizes scripts by the interpreter named in their `#!` line, including when it's
+	/// run through `env`, like `#!/usr/bin/env -S python3 -u`.
+	pub fn from_shebang(line: &str) -> Option<(Self, &'static str)> {
+		let mut words = line.strip_prefix("#!")?.split_whitespace();
+		let mut program = words.next()?.rsplit('/').next()?;
+		if program == "env" {
+			program = words.find(|word| !word.starts_with('-') && !word.contains('='))?;
 		}
+
+		// Versioned interpreters, like `python3.12`, are the same language as the unversioned one
+		let program = program
+			.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+			.to_ascii_lowercase();
+		find(INTERPRETERS, &program).map(|(program, language)| (*language, *program))
 	}
 
 	pub fn from_extension<S>(ext: S) -> Option<Self>
 	where
 		S: AsRef<OsStr>,
 	{
-		use Language::*;
+		let ext = ext.as_ref().to_str()?.to_ascii_lowercase();
+		lookup(EXTENSIONS, &ext)
+	}
 
-		match ext.as_ref().to_str()?.to_ascii_lowercase().as_ref() {
-			"adb" => Some(Ada),
-			"ads" => Some(Ada),
-			"asm" => Some(Assembly),
-			"astro" => Some(Astro),
-			"b" => Some(Brainfuck),
-			"bat" => Some(Batch),
-			"bqn" => Some(Bqn),
-			"c" => Some(C),
-			"carbon" => Some(Carbon),
-			"cbl" => Some(Cobol),
-			"cc" => Some(Cxx),
-			"cjs" => Some(JavaScript),
-			"cl" => Some(CommonLisp),
-			"clj" => Some(Clojure),
-			"cljc" => Some(Clojure),
-			"cljs" => Some(Clojure),
-			"cob" => Some(Cobol),
-			"coffee" => Some(CoffeeScript),
-			"cpp" => Some(Cxx),
-			"cpp2" => Some(Cxx),
-			"cpy" => Some(Cobol),
-			"cr" => Some(Crystal),
-			"cs" => Some(CSharp),
-			"csproj" => Some(Xml),
-			"css" => Some(Css),
-			"cts" => Some(TypeScript),
-			"cue" => Some(Cue),
-			"cxx" => Some(Cxx),
-			"c3" => Some(C3),
-			"d" => Some(D),
-			"dart" => Some(Dart),
-			"dhall" => Some(Dhall),
-			"elm" => Some(Elm),
-			"erl" => Some(Erlang),
-			"ex" => Some(Elixir),
-			"exs" => Some(Elixir),
-			"f" => Some(Fortran),
-			"fish" => Some(Fish),
-			"for" => Some(Fortran),
-			"fs" => Some(FSharp),
-			"fsproj" => Some(Xml),
-			"f90" => Some(Fortran),
-			"f95" => Some(Fortran),
-			"f03" => Some(Fortran),
-			"gleam" => Some(Gleam),
-			"gn" => Some(Gn),
-			"go" => Some(Go),
-			"gql" => Some(GraphQl),
-			"gr" => Some(Grain),
-			"gren" => Some(Gren),
-			"h" => Some(C),
-			"ha" => Some(Hare),
-			"hh" => Some(Cxx),
-			"hpp" => Some(Cxx),
-			"hs" => Some(Haskell),
-			"htm" => Some(Html),
-			"html" => Some(Html),
-			"hx" => Some(Haxe),
-			"hxx" => Some(Cxx),
-			"idr" => Some(Idris),
-			"io" => Some(Io),
-			"jai" => Some(Jai),
-			"java" => Some(Java),
-			"jl" => Some(Julia),
-			"js" => Some(JavaScript),
-			"json" => Some(Json),
-			"jsonc" => Some(Json),
-			"jsx" => Some(JavaScript),
-			"kk" => Some(Koka),
-			"kt" => Some(Kotlin),
-			"kts" => Some(Kotlin),
-			"l" => Some(CommonLisp),
-			"lisp" => Some(CommonLisp),
-			"ll" => Some(Llvm),
-			"lsp" => Some(CommonLisp),
-			"lua" => Some(Lua),
-			"m" => Some(ObjectiveC),
-			"md" => Some(Markdown),
-			"metal" => Some(Metal),
-			"mjs" => Some(JavaScript),
-			"mk" => Some(Make),
-			"ml" => Some(OCaml),
-			"mli" => Some(OCaml),
-			"mm" => Some(ObjectiveCxx),
-			"mts" => Some(TypeScript),
-			"nim" => Some(Nim),
-			"nix" => Some(Nix),
-			"nu" => Some(NuShell),
-			"odin" => Some(Odin),
-			"pas" => Some(Pascal),
-			"php" => Some(Php),
-			"pl" => Some(Perl),
-			"pm" => Some(Perl),
-			"pl6" => Some(Raku),
-			"pm6" => Some(Raku),
-			"pro" => Some(Prolog),
-			"ps1" => Some(PowerShell),
-			"purs" => Some(PureScript),
-			"py" => Some(Python),
-			"p6" => Some(Raku),
-			"r" => Some(R),
-			"raku" => Some(Raku),
-			"rakumod" => Some(Raku),
-			"rb" => Some(Ruby),
-			"re" => Some(Reason),
-			"ren" => Some(Ren),
-			"res" => Some(ReScript),
-			"rkt" => Some(Racket),
-			"roc" => Some(Roc),
-			"rs" => Some(Rust),
-			"s" => Some(Assembly),
-			"sass" => Some(Sass),
-			"scss" => Some(Scss),
-			"scala" => Some(Scala),
-			"scm" => Some(Scheme),
-			"sh" => Some(Bash),
-			"sql" => Some(Sql),
-			"svelte" => Some(Svelte),
-			"swift" => Some(Swift),
-			"tcl" => Some(Tcl),
-			"tf" => Some(Terraform),
-			"toml" => Some(Toml),
-			"ts" => Some(TypeScript),
-			"tsx" => Some(TypeScript),
-			"u" => Some(Unison),
-			"v" => Some(V),
-			"val" => Some(Hylo),
-			"vala" => Some(Vala),
-			"vale" => Some(Vale),
-			"vb" => Some(VisualBasic),
-			"vbproj" => Some(Xml),
-			"vbs" => Some(VisualBasic),
-			"vue" => Some(Vue),
-			"wat" => Some(WebAssembly),
-			"wren" => Some(Wren),
-			"xml" => Some(Xml),
-			"yall" => Some(Yall),
-			"yaml" => Some(Yaml),
-			"yml" => Some(Yaml),
-			"yue" => Some(YueScript),
-			"zig" => Some(Zig),
-			_ => None,
+	/// The identifiers accepted by `from_name`
+	pub fn aliases(&self) -> impl Iterator<Item = &'static str> {
+		identifiers(NAMES, *self)
+	}
+
+	/// The extensions accepted by `from_extension`, without a leading dot
+	pub fn extensions(&self) -> impl Iterator<Item = &'static str> {
+		identifiers(EXTENSIONS, *self)
+	}
+
+	/// Exact file names which are recognized without looking at their extension
+	pub fn file_names(&self) -> impl Iterator<Item = &'static str> {
+		identifiers(FILE_NAMES, *self)
+	}
+
+	/// Interpreters which identify the language when they're named in a shebang line
+	pub fn interpreters(&self) -> impl Iterator<Item = &'static str> {
+		identifiers(INTERPRETERS, *self)
+	}
+}
+
+/// The rule which decided what language a file is written in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rule {
+	FileName(&'static str),
+	Extension(&'static str),
+	Shebang(&'static str),
+}
+
+impl Display for Rule {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Rule::FileName(name) => write!(f, "its file name \"{name}\""),
+			Rule::Extension(ext) => write!(f, "its extension \".{ext}\""),
+			Rule::Shebang(program) => write!(f, "its shebang, which runs \"{program}\""),
 		}
 	}
 }
 
+fn find(
+	table: &'static [(&'static str, Language)],
+	key: &str,
+) -> Option<&'static (&'static str, Language)> {
+	table.iter().find(|(it, _)| *it == key)
+}
+
+fn lookup(table: &'static [(&'static str, Language)], key: &str) -> Option<Language> {
+	find(table, key).map(|(_, language)| *language)
+}
+
+fn identifiers(
+	table: &'static [(&'static str, Language)],
+	language: Language,
+) -> impl Iterator<Item = &'static str> {
+	table
+		.iter()
+		.filter(move |(_, it)| *it == language)
+		.map(|(identifier, _)| *identifier)
+}
+
+/// Names (in lowercase) which can be used to refer to a language, like with `--exclude`
+const NAMES: &[(&str, Language)] = {
+	use Language::*;
+	&[
+		("ada", Ada),
+		("assembly", Assembly),
+		("astro", Astro),
+		("bash", Bash),
+		("batch", Batch),
+		("bqn", Bqn),
+		("brainfuck", Brainfuck),
+		("c", C),
+		("carbon", Carbon),
+		("clojure", Clojure),
+		("cmake", CMake),
+		("cobol", Cobol),
+		("coffeescript", CoffeeScript),
+		("commonlisp", CommonLisp),
+		("crystal", Crystal),
+		("csharp", CSharp),
+		("c3", C3),
+		("c#", CSharp),
+		("css", Css),
+		("cue", Cue),
+		("c++", Cxx),
+		("d", D),
+		("dart", Dart),
+		("dhall", Dhall),
+		("dockerfile", Dockerfile),
+		("elm", Elm),
+		("erlang", Erlang),
+		("elixir", Elixir),
+		("fortran", Fortran),
+		("fish", Fish),
+		("fsharp", FSharp),
+		("f#", FSharp),
+		("gleam", Gleam),
+		("gn", Gn),
+		("go", Go),
+		("graphql", GraphQl),
+		("grain", Grain),
+		("gren", Gren),
+		("hare", Hare),
+		("haskell", Haskell),
+		("haxe", Haxe),
+		("html", Html),
+		("hylo", Hylo),
+		("idris", Idris),
+		("io", Io),
+		("jai", Jai),
+		("java", Java),
+		("julia", Julia),
+		("javascript", JavaScript),
+		("json", Json),
+		("koka", Koka),
+		("kotlin", Kotlin),
+		("llvm", Llvm),
+		("lua", Lua),
+		("objectivec", ObjectiveC),
+		("objective-c", ObjectiveC),
+		("markdown", Markdown),
+		("make", Make),
+		("metal", Metal),
+		("ocaml", OCaml),
+		("objectivec++", ObjectiveCxx),
+		("objective-c++", ObjectiveCxx),
+		("nim", Nim),
+		("nix", Nix),
+		("nushell", NuShell),
+		("odin", Odin),
+		("pascal", Pascal),
+		("php", Php),
+		("perl", Perl),
+		("prolog", Prolog),
+		("powershell", PowerShell),
+		("purescript", PureScript),
+		("python", Python),
+		("r", R),
+		("racket", Racket),
+		("raku", Raku),
+		("reason", Reason),
+		("reasonml", Reason),
+		("ren", Ren),
+		("rescript", ReScript),
+		("roc", Roc),
+		("ruby", Ruby),
+		("rust", Rust),
+		("sass", Sass),
+		("scss", Scss),
+		("scala", Scala),
+		("scheme", Scheme),
+		("sf", Seafoam),
+		("sql", Sql),
+		("svelte", Svelte),
+		("swift", Swift),
+		("tcl", Tcl),
+		("terraform", Terraform),
+		("toml", Toml),
+		("typescript", TypeScript),
+		("unison", Unison),
+		("v", V),
+		("vala", Vala),
+		("vale", Vale),
+		("visualbasic", VisualBasic),
+		("vue", Vue),
+		("webassembly", WebAssembly),
+		("wren", Wren),
+		("xml", Xml),
+		("yall", Yall),
+		("yaml", Yaml),
+		("yuescript", YueScript),
+		("zig", Zig),
+	]
+};
+
+/// File names which identify a language on their own, regardless of their extension
+const FILE_NAMES: &[(&str, Language)] = {
+	use Language::*;
+	&[
+		("Cakefile", CoffeeScript),
+		("CMakeLists.txt", CMake),
+		("Dockerfile", Dockerfile),
+		("Makefile", Make),
+		("Rakefile", Ruby),
+	]
+};
+
+/// File extensions (in lowercase, without a leading dot) and the languages they belong to
+const EXTENSIONS: &[(&str, Language)] = {
+	use Language::*;
+	&[
+		("adb", Ada),
+		("ads", Ada),
+		("asm", Assembly),
+		("astro", Astro),
+		("b", Brainfuck),
+		("bat", Batch),
+		("bqn", Bqn),
+		("c", C),
+		("carbon", Carbon),
+		("cbl", Cobol),
+		("cc", Cxx),
+		("cjs", JavaScript),
+		("cl", CommonLisp),
+		("clj", Clojure),
+		("cljc", Clojure),
+		("cljs", Clojure),
+		("cob", Cobol),
+		("coffee", CoffeeScript),
+		("cpp", Cxx),
+		("cpp2", Cxx),
+		("cpy", Cobol),
+		("cr", Crystal),
+		("cs", CSharp),
+		("csproj", Xml),
+		("css", Css),
+		("cts", TypeScript),
+		("cue", Cue),
+		("cxx", Cxx),
+		("c3", C3),
+		("d", D),
+		("dart", Dart),
+		("dhall", Dhall),
+		("elm", Elm),
+		("erl", Erlang),
+		("ex", Elixir),
+		("exs", Elixir),
+		("f", Fortran),
+		("fish", Fish),
+		("for", Fortran),
+		("fs", FSharp),
+		("fsproj", Xml),
+		("f90", Fortran),
+		("f95", Fortran),
+		("f03", Fortran),
+		("gleam", Gleam),
+		("gn", Gn),
+		("go", Go),
+		("gql", GraphQl),
+		("gr", Grain),
+		("gren", Gren),
+		("h", C),
+		("ha", Hare),
+		("hh", Cxx),
+		("hpp", Cxx),
+		("hs", Haskell),
+		("htm", Html),
+		("html", Html),
+		("hx", Haxe),
+		("hxx", Cxx),
+		("idr", Idris),
+		("io", Io),
+		("jai", Jai),
+		("java", Java),
+		("jl", Julia),
+		("js", JavaScript),
+		("json", Json),
+		("jsonc", Json),
+		("jsx", JavaScript),
+		("kk", Koka),
+		("kt", Kotlin),
+		("kts", Kotlin),
+		("l", CommonLisp),
+		("lisp", CommonLisp),
+		("ll", Llvm),
+		("lsp", CommonLisp),
+		("lua", Lua),
+		("m", ObjectiveC),
+		("md", Markdown),
+		("metal", Metal),
+		("mjs", JavaScript),
+		("mk", Make),
+		("ml", OCaml),
+		("mli", OCaml),
+		("mm", ObjectiveCxx),
+		("mts", TypeScript),
+		("nim", Nim),
+		("nix", Nix),
+		("nu", NuShell),
+		("odin", Odin),
+		("pas", Pascal),
+		("php", Php),
+		("pl", Perl),
+		("pm", Perl),
+		("pl6", Raku),
+		("pm6", Raku),
+		("pro", Prolog),
+		("ps1", PowerShell),
+		("purs", PureScript),
+		("py", Python),
+		("p6", Raku),
+		("r", R),
+		("raku", Raku),
+		("rakumod", Raku),
+		("rb", Ruby),
+		("re", Reason),
+		("ren", Ren),
+		("res", ReScript),
+		("rkt", Racket),
+		("roc", Roc),
+		("rs", Rust),
+		("s", Assembly),
+		("sass", Sass),
+		("scss", Scss),
+		("scala", Scala),
+		("scm", Scheme),
+		("sh", Bash),
+		("sql", Sql),
+		("svelte", Svelte),
+		("swift", Swift),
+		("tcl", Tcl),
+		("tf", Terraform),
+		("toml", Toml),
+		("ts", TypeScript),
+		("tsx", TypeScript),
+		("u", Unison),
+		("v", V),
+		("val", Hylo),
+		("vala", Vala),
+		("vale", Vale),
+		("vb", VisualBasic),
+		("vbproj", Xml),
+		("vbs", VisualBasic),
+		("vue", Vue),
+		("wat", WebAssembly),
+		("wren", Wren),
+		("xml", Xml),
+		("yall", Yall),
+		("yaml", Yaml),
+		("yml", Yaml),
+		("yue", YueScript),
+		("zig", Zig),
+	]
+};
+
+/// Interpreters (in lowercase, without a version number) which are recognized in shebang lines
+const INTERPRETERS: &[(&str, Language)] = {
+	use Language::*;
+	&[
+		("bash", Bash),
+		("crystal", Crystal),
+		("dart", Dart),
+		("dash", Bash),
+		("elixir", Elixir),
+		("escript", Erlang),
+		("fish", Fish),
+		("guile", Scheme),
+		("julia", Julia),
+		("ksh", Bash),
+		("lua", Lua),
+		("luajit", Lua),
+		("make", Make),
+		("node", JavaScript),
+		("nu", NuShell),
+		("ocaml", OCaml),
+		("perl", Perl),
+		("php", Php),
+		("pwsh", PowerShell),
+		("python", Python),
+		("racket", Racket),
+		("raku", Raku),
+		("rscript", R),
+		("ruby", Ruby),
+		("runhaskell", Haskell),
+		("sbcl", CommonLisp),
+		("scala", Scala),
+		("sh", Bash),
+		("swift", Swift),
+		("tclsh", Tcl),
+		("ts-node", TypeScript),
+		("wish", Tcl),
+		("zsh", Bash),
+	]
+};
+
+/// A broad grouping of languages, so that things like configuration files and documentation
+/// can be told apart from (or filtered out of) the code in a project.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Category {
+	Programming,
+	Markup,
+	Data,
+	Prose,
+	Config,
+	Build,
+}
+
+impl FromStr for Category {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_ref() {
+			"programming" | "code" => Ok(Self::Programming),
+			"markup" => Ok(Self::Markup),
+			"data" => Ok(Self::Data),
+			"prose" | "docs" => Ok(Self::Prose),
+			"config" | "configuration" => Ok(Self::Config),
+			"build" => Ok(Self::Build),
+			_ => Err(()),
+		}
+	}
+}
+
+impl Display for Category {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = match self {
+			Self::Programming => "programming",
+			Self::Markup => "markup",
+			Self::Data => "data",
+			Self::Prose => "prose",
+			Self::Config => "config",
+			Self::Build => "build",
+		};
+		write!(f, "{name}")
+	}
+}
+
+impl Category {
+	pub fn help() -> &'static str {
+		r#""programming", "markup", "data", "prose", "config", "build""#
+	}
+}
+
 #[derive(Clone, Debug)]
 pub struct LanguageInfo {
 	pub name: String,
 	pub color: Option<Color>,
+	/// Only `None` for the "Other" row, which can be made up of languages from any category
+	pub category: Option<Category>,
 }
 
 macro_rules! info {
-	( $n:expr $(,)? ) => {{
-		LanguageInfo {
+	( $n:expr $(, color: $c:expr)?, category: $cat:ident $(,)? ) => {{
+		#[allow(unused_mut)]
+		let mut info = LanguageInfo {
 			name: $n.into(),
 			color: None,
-		}
-	}};
-
-	( $n:expr , color: $c:expr $(,)? ) => {{
-		LanguageInfo {
-			name: $n.into(),
-			color: Some($c.into()),
-		}
+			category: Some(Category::$cat),
+		};
+		$( info.color = Some($c.into()); )?
+		info
 	}};
 }
 
@@ -444,132 +641,449 @@ impl LanguageInfo {
 		use Language::*;
 
 		match lang {
-			Ada => info!("Ada", color: 0x02f88c),
-			Assembly => info!("Assembly"),
-			Astro => info!("Astro", color: 0xa78bfa),
-			Bash => info!("Bash", color: [50, 50, 50]),
-			Batch => info!("Batch"),
-			Bqn => info!("BQN", color: 0x3D6C64),
-			Brainfuck => info!("Brainfuck"),
+			Ada => info!("Ada", color: 0x02f88c, category: Programming),
+			Assembly => info!("Assembly", category: Programming),
+			Astro => info!("Astro", color: 0xa78bfa, category: Programming),
+			Bash => info!("Bash", color: [50, 50, 50], category: Programming),
+			Batch => info!("Batch", category: Programming),
+			Bqn => info!("BQN", color: 0x3D6C64, category: Programming),
+			Brainfuck => info!("Brainfuck", category: Programming),
 			// C => info!("C", color: [163, 176, 240]),
-			C => info!("C", color: [40, 48, 126]),
-			Carbon => info!("Carbon"),
-			Clojure => info!("Clojure", color: [0, 112, 255]),
-			CMake => info!("CMake"),
-			Cobol => info!("Cobol", color: [0, 112, 255]),
-			CoffeeScript => info!("CoffeeScript", color: 0x3e2723),
-			CommonLisp => info!("CommonLisp", color: 0x3fb68b),
-			Crystal => info!("Crystal", color: 0x000000),
-			CSharp => info!("C#", color: [5, 142, 12]),
-			Css => info!("CSS", color: 0x563d7c),
-			Cue => info!("Cue"),
-			Cxx => info!("C++", color: [25, 65, 122]),
-			C3 => info!("C3", color: 0x355beb),
-			D => info!("D", color: 0xb03931),
-			Dart => info!("Dart", color: 0x40c4ff),
-			Dhall => info!("Dhall"),
-			Dockerfile => info!("Dockerfile", color: 0x003f8c),
-			Elm => info!("Elm", color: 0x60b5cc),
-			Elixir => info!("Elixir", color: 0x4e2a8e),
-			Erlang => info!("Erlang", color: 0xa2003e),
-			Fish => info!("Fish", color: 0x75147c),
-			Fortran => info!("Fortran"),
-			FSharp => info!("F#", color: 0xb845fc),
-			Gleam => info!("Gleam", color: 0xffaff3),
-			Gn => info!("gn"),
-			Go => info!("Go", color: 0x00add8),
-			Grain => info!("Grain", color: [255, 133, 14]),
-			GraphQl => info!("GraphQL", color: 0xe10098),
-			Gren => info!("Gren", color: 0xff6600),
-			Hare => info!("Hare", color: 0x121415),
-			Haskell => info!("Haskell", color: 0x6144b3),
-			Haxe => info!("Haxe", color: 0xf68712),
-			Html => info!("HTML", color: 0xdf6e3c),
-			Hylo => info!("Hylo", color: [0, 119, 179]),
-			Idris => info!("Idris", color: 0xc74350),
-			Io => info!("Io", color: 0xa9188d),
-			Jai => info!("Jai"),
+			C => info!("C", color: [40, 48, 126], category: Programming),
+			Carbon => info!("Carbon", category: Programming),
+			Clojure => info!("Clojure", color: [0, 112, 255], category: Programming),
+			CMake => info!("CMake", category: Build),
+			Cobol => info!("Cobol", color: [0, 112, 255], category: Programming),
+			CoffeeScript => info!("CoffeeScript", color: 0x3e2723, category: Programming),
+			CommonLisp => info!("CommonLisp", color: 0x3fb68b, category: Programming),
+			Crystal => info!("Crystal", color: 0x000000, category: Programming),
+			CSharp => info!("C#", color: [5, 142, 12], category: Programming),
+			Css => info!("CSS", color: 0x563d7c, category: Markup),
+			Cue => info!("Cue", category: Config),
+			Cxx => info!("C++", color: [25, 65, 122], category: Programming),
+			C3 => info!("C3", color: 0x355beb, category: Programming),
+			D => info!("D", color: 0xb03931, category: Programming),
+			Dart => info!("Dart", color: 0x40c4ff, category: Programming),
+			Dhall => info!("Dhall", category: Config),
+			Dockerfile => info!("Dockerfile", color: 0x003f8c, category: Build),
+			Elm => info!("Elm", color: 0x60b5cc, category: Programming),
+			Elixir => info!("Elixir", color: 0x4e2a8e, category: Programming),
+			Erlang => info!("Erlang", color: 0xa2003e, category: Programming),
+			Fish => info!("Fish", color: 0x75147c, category: Programming),
+			Fortran => info!("Fortran", category: Programming),
+			FSharp => info!("F#", color: 0xb845fc, category: Programming),
+			Gleam => info!("Gleam", color: 0xffaff3, category: Programming),
+			Gn => info!("gn", category: Build),
+			Go => info!("Go", color: 0x00add8, category: Programming),
+			Grain => info!("Grain", color: [255, 133, 14], category: Programming),
+			GraphQl => info!("GraphQL", color: 0xe10098, category: Data),
+			Gren => info!("Gren", color: 0xff6600, category: Programming),
+			Hare => info!("Hare", color: 0x121415, category: Programming),
+			Haskell => info!("Haskell", color: 0x6144b3, category: Programming),
+			Haxe => info!("Haxe", color: 0xf68712, category: Programming),
+			Html => info!("HTML", color: 0xdf6e3c, category: Markup),
+			Hylo => info!("Hylo", color: [0, 119, 179], category: Programming),
+			Idris => info!("Idris", color: 0xc74350, category: Programming),
+			Io => info!("Io", color: 0xa9188d, category: Programming),
+			Jai => info!("Jai", category: Programming),
 			// Java => info!("Java", color: [205, 112, 42]),
-			Java => info!("Java", color: [205, 55, 47]),
-			JavaScript => info!("JavaScript", color: 0xf1e05a),
-			Json => info!("JSON"),
-			Julia => info!("Julia", color: 0xa270ba),
-			Koka => info!("Koka"),
-			Kotlin => info!("Kotlin", color: 0xa97bff),
-			Llvm => info!("LLVM IR"),
-			Lua => info!("Lua", color: 0x000077),
-			Make => info!("Make"),
-			Markdown => info!("Markdown"),
-			Metal => info!("Metal", color: 0xca495d),
-			Nim => info!("Nim", color: 0xffc200),
-			Nix => info!("Nix", color: 0x5773b8),
-			NuShell => info!("NuShell", color: 0x3aa675),
-			ObjectiveC => info!("Objective-C"),
-			ObjectiveCxx => info!("Objective-C++"),
-			OCaml => info!("OCaml", color: 0xee6a1a),
-			Odin => info!("Odin", color: 0x3882d2),
-			Pascal => info!("Pascal"),
-			Perl => info!("Perl", color: 0x0073a1),
-			Php => info!("PHP", color: 0x4f5d95),
-			PowerShell => info!("PowerShell"),
-			Prolog => info!("Prolog"),
-			PureScript => info!("PureScript"),
-			Python => info!("Python", color: 0x3776ab),
-			R => info!("R", color: 0x198ce7),
-			Racket => info!("Racket"),
-			Raku => info!("Raku", color: 0xd0dd2b),
-			Reason => info!("Reason", color: 0xdb4d3f),
-			Ren => info!("Ren", color: 0xdd5e36),
-			ReScript => info!("ReScript", color: 0xD55454),
-			Ruby => info!("Ruby", color: 0xcc342d),
-			Roc => info!("Roc", color: 0x7c59dd),
-			Rust => info!("Rust", color: 0xa72145),
-			Sass => info!("Sass", color: 0xcf649a),
-			Scss => info!("SCSS", color: 0xc6538c),
-			Scala => info!("Scala", color: 0xc6422f),
-			Scheme => info!("Scheme"),
-			Seafoam => info!("Seafoam", color: 0x90eada),
-			Sql => info!("SQL", color: 0x336790),
-			Svelte => info!("Svelte", color: 0xe44d26),
-			Swift => info!("Swift", color: 0xf05138),
-			Tcl => info!("Tcl"),
-			Terraform => info!("Terraform", color: 0x844fba),
-			Toml => info!("TOML"),
-			TypeScript => info!("TypeScript", color: 0x3178c6),
-			Unison => info!("Unison", color: [118, 207, 143]),
-			V => info!("V"),
-			Vala => info!("Vala", color: 0x7239b3),
-			Vale => info!("Vale"),
-			VisualBasic => info!("Visual Basic"),
-			Vue => info!("Vue", color: 0x41b883),
-			WebAssembly => info!("WebAssembly", color: 0x654ff0),
-			Wren => info!("Wren", color: 0x383838),
-			Xml => info!("XML"),
-			Yall => info!("Y'all", color: 0xff8f77),
-			Yaml => info!("YAML"),
-			YueScript => info!("YueScript", color: 0xb7ae8f),
-			Zig => info!("Zig", color: 0xeba842),
+			Java => info!("Java", color: [205, 55, 47], category: Programming),
+			JavaScript => info!("JavaScript", color: 0xf1e05a, category: Programming),
+			Json => info!("JSON", category: Data),
+			Julia => info!("Julia", color: 0xa270ba, category: Programming),
+			Koka => info!("Koka", category: Programming),
+			Kotlin => info!("Kotlin", color: 0xa97bff, category: Programming),
+			Llvm => info!("LLVM IR", category: Programming),
+			Lua => info!("Lua", color: 0x000077, category: Programming),
+			Make => info!("Make", category: Build),
+			Markdown => info!("Markdown", category: Prose),
+			Metal => info!("Metal", color: 0xca495d, category: Programming),
+			Nim => info!("Nim", color: 0xffc200, category: Programming),
+			Nix => info!("Nix", color: 0x5773b8, category: Programming),
+			NuShell => info!("NuShell", color: 0x3aa675, category: Programming),
+			ObjectiveC => info!("Objective-C", category: Programming),
+			ObjectiveCxx => info!("Objective-C++", category: Programming),
+			OCaml => info!("OCaml", color: 0xee6a1a, category: Programming),
+			Odin => info!("Odin", color: 0x3882d2, category: Programming),
+			Pascal => info!("Pascal", category: Programming),
+			Perl => info!("Perl", color: 0x0073a1, category: Programming),
+			Php => info!("PHP", color: 0x4f5d95, category: Programming),
+			PowerShell => info!("PowerShell", category: Programming),
+			Prolog => info!("Prolog", category: Programming),
+			PureScript => info!("PureScript", category: Programming),
+			Python => info!("Python", color: 0x3776ab, category: Programming),
+			R => info!("R", color: 0x198ce7, category: Programming),
+			Racket => info!("Racket", category: Programming),
+			Raku => info!("Raku", color: 0xd0dd2b, category: Programming),
+			Reason => info!("Reason", color: 0xdb4d3f, category: Programming),
+			Ren => info!("Ren", color: 0xdd5e36, category: Programming),
+			ReScript => info!("ReScript", color: 0xD55454, category: Programming),
+			Ruby => info!("Ruby", color: 0xcc342d, category: Programming),
+			Roc => info!("Roc", color: 0x7c59dd, category: Programming),
+			Rust => info!("Rust", color: 0xa72145, category: Programming),
+			Sass => info!("Sass", color: 0xcf649a, category: Markup),
+			Scss => info!("SCSS", color: 0xc6538c, category: Markup),
+			Scala => info!("Scala", color: 0xc6422f, category: Programming),
+			Scheme => info!("Scheme", category: Programming),
+			Seafoam => info!("Seafoam", color: 0x90eada, category: Programming),
+			Sql => info!("SQL", color: 0x336790, category: Data),
+			Svelte => info!("Svelte", color: 0xe44d26, category: Programming),
+			Swift => info!("Swift", color: 0xf05138, category: Programming),
+			Tcl => info!("Tcl", category: Programming),
+			Terraform => info!("Terraform", color: 0x844fba, category: Config),
+			Toml => info!("TOML", category: Config),
+			TypeScript => info!("TypeScript", color: 0x3178c6, category: Programming),
+			Unison => info!("Unison", color: [118, 207, 143], category: Programming),
+			V => info!("V", category: Programming),
+			Vala => info!("Vala", color: 0x7239b3, category: Programming),
+			Vale => info!("Vale", category: Programming),
+			VisualBasic => info!("Visual Basic", category: Programming),
+			Vue => info!("Vue", color: 0x41b883, category: Programming),
+			WebAssembly => info!("WebAssembly", color: 0x654ff0, category: Programming),
+			Wren => info!("Wren", color: 0x383838, category: Programming),
+			Xml => info!("XML", category: Markup),
+			Yall => info!("Y'all", color: 0xff8f77, category: Programming),
+			Yaml => info!("YAML", category: Config),
+			YueScript => info!("YueScript", color: 0xb7ae8f, category: Programming),
+			Zig => info!("Zig", color: 0xeba842, category: Programming),
+		}
+	}
+}
+
+/// The markers that begin comments in a language, used to tell comment lines apart from
+/// code. Block comment markers are checked before line comment markers, since some languages
+/// (like Lua's `--[[`) start their block comments with their line comment marker.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CommentSyntax {
+	pub line: &'static [&'static str],
+	pub block: &'static [(&'static str, &'static str)],
+}
+
+const NO_COMMENTS: CommentSyntax = CommentSyntax {
+	line: &[],
+	block: &[],
+};
+const C_STYLE: CommentSyntax = CommentSyntax {
+	line: &["//"],
+	block: &[("/*", "*/")],
+};
+const SLASH: CommentSyntax = CommentSyntax {
+	line: &["//"],
+	block: &[],
+};
+const HASH: CommentSyntax = CommentSyntax {
+	line: &["#"],
+	block: &[],
+};
+const DASH: CommentSyntax = CommentSyntax {
+	line: &["--"],
+	block: &[],
+};
+const ML_STYLE: CommentSyntax = CommentSyntax {
+	line: &["--"],
+	block: &[("{-", "-}")],
+};
+const LISP_STYLE: CommentSyntax = CommentSyntax {
+	line: &[";"],
+	block: &[("#|", "|#")],
+};
+const MARKUP: CommentSyntax = CommentSyntax {
+	line: &[],
+	block: &[("<!--", "-->")],
+};
+const COMPONENT: CommentSyntax = CommentSyntax {
+	line: &["//"],
+	block: &[("<!--", "-->"), ("/*", "*/")],
+};
+
+impl Language {
+	pub fn comment_syntax(&self) -> CommentSyntax {
+		use Language::*;
+
+		match self {
+			Ada => DASH,
+			Assembly => CommentSyntax {
+				line: &[";", "#"],
+				block: &[],
+			},
+			Astro | Svelte | Vue => COMPONENT,
+			Bash | Crystal | Dockerfile | Elixir | Fish | Gn | Make | NuShell | Perl | Python | R
+			| Raku | Roc | Tcl | Toml | Yaml | Bqn => HASH,
+			Batch => CommentSyntax {
+				line: &["::", "REM ", "rem "],
+				block: &[],
+			},
+			Brainfuck | Json | Seafoam | Yall => NO_COMMENTS,
+			C | C3 | CSharp | Cxx | Dart | Go | Grain | Haxe | Java | JavaScript | Jai | Kotlin
+			| Koka | Metal | ObjectiveC | ObjectiveCxx | Odin | Reason | ReScript | Rust | Sass
+			| Scss | Scala | Swift | TypeScript | V | Vala | Vale | Wren => C_STYLE,
+			Carbon | Cue | Gleam | Hare | Hylo | Ren | Zig => SLASH,
+			Clojure | Llvm => CommentSyntax {
+				line: &[";"],
+				block: &[],
+			},
+			CMake => CommentSyntax {
+				line: &["#"],
+				block: &[("#[[", "]]")],
+			},
+			Cobol => CommentSyntax {
+				line: &["*>"],
+				block: &[],
+			},
+			CoffeeScript => CommentSyntax {
+				line: &["#"],
+				block: &[("###", "###")],
+			},
+			CommonLisp | Racket | Scheme => LISP_STYLE,
+			Css => CommentSyntax {
+				line: &[],
+				block: &[("/*", "*/")],
+			},
+			D => CommentSyntax {
+				line: &["//"],
+				block: &[("/*", "*/"), ("/+", "+/")],
+			},
+			Dhall | Elm | Gren | Haskell | Idris | PureScript | Unison => ML_STYLE,
+			Erlang => CommentSyntax {
+				line: &["%"],
+				block: &[],
+			},
+			Fortran => CommentSyntax {
+				line: &["!"],
+				block: &[],
+			},
+			FSharp => CommentSyntax {
+				line: &["//"],
+				block: &[("(*", "*)")],
+			},
+			GraphQl => HASH,
+			Html | Markdown | Xml => MARKUP,
+			Io => CommentSyntax {
+				line: &["//", "#"],
+				block: &[("/*", "*/")],
+			},
+			Julia => CommentSyntax {
+				line: &["#"],
+				block: &[("#=", "=#")],
+			},
+			Lua => CommentSyntax {
+				line: &["--"],
+				block: &[("--[[", "]]")],
+			},
+			Nim => CommentSyntax {
+				line: &["#"],
+				block: &[("#[", "]#")],
+			},
+			Nix => CommentSyntax {
+				line: &["#"],
+				block: &[("/*", "*/")],
+			},
+			OCaml => CommentSyntax {
+				line: &[],
+				block: &[("(*", "*)")],
+			},
+			Pascal => CommentSyntax {
+				line: &["//"],
+				block: &[("(*", "*)"), ("{", "}")],
+			},
+			Php => CommentSyntax {
+				line: &["//", "#"],
+				block: &[("/*", "*/")],
+			},
+			PowerShell => CommentSyntax {
+				line: &["#"],
+				block: &[("<#", "#>")],
+			},
+			Prolog => CommentSyntax {
+				line: &["%"],
+				block: &[("/*", "*/")],
+			},
+			Ruby => CommentSyntax {
+				line: &["#"],
+				block: &[("=begin", "=end")],
+			},
+			Sql => CommentSyntax {
+				line: &["--"],
+				block: &[("/*", "*/")],
+			},
+			Terraform => CommentSyntax {
+				line: &["#", "//"],
+				block: &[("/*", "*/")],
+			},
+			VisualBasic => CommentSyntax {
+				line: &["'", "REM "],
+				block: &[],
+			},
+			WebAssembly => CommentSyntax {
+				line: &[";;"],
+				block: &[("(;", ";)")],
+			},
+			YueScript => DASH,
 		}
 	}
 }
 
 #[derive(Clone, Debug)]
 pub struct LanguageSummary {
-	pub language: Language,
+	/// `None` for the "Other" row, which has every language that was rolled up by `--top` or
+	/// `--min-percent`
+	pub language: Option<Language>,
 	pub lines: usize,
 	pub blank_lines: usize,
-	pub files: Vec<PathBuf>,
+	pub comment_lines: usize,
+	pub test_lines: usize,
+	pub files: Vec<FileSummary>,
 }
 
 impl LanguageSummary {
 	pub fn from(language: Language) -> Self {
 		Self {
-			language,
+			language: Some(language),
+			..Self::other()
+		}
+	}
+
+	/// An empty "Other" row, for languages to be rolled up into
+	pub fn other() -> Self {
+		Self {
+			language: None,
 			lines: 0,
 			blank_lines: 0,
+			comment_lines: 0,
+			test_lines: 0,
 			files: vec![],
 		}
 	}
+
+	pub fn info(&self) -> LanguageInfo {
+		match self.language {
+			Some(language) => language.info(),
+			None => LanguageInfo {
+				name: "Other".into(),
+				color: None,
+				category: None,
+			},
+		}
+	}
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct FileSummary {
+	pub path: PathBuf,
+	pub lines: usize,
+	pub blank_lines: usize,
+	pub comment_lines: usize,
+}
+
+#[cfg(test)]
+impl FileSummary {
+	/// A file with nothing in it, for tests which only care about which files there are
+	pub fn named(path: &str) -> Self {
+		Self {
+			path: path.into(),
+			..Default::default()
+		}
+	}
+}
+
+impl LanguageSummary {
+	/// Adds the counts and files of another summary into this one
+	pub fn merge(&mut self, other: LanguageSummary) {
+		self.lines += other.lines;
+		self.blank_lines += other.blank_lines;
+		self.comment_lines += other.comment_lines;
+		self.test_lines += other.test_lines;
+		self.files.extend(other.files);
+	}
+
+	/// Counts a file towards this summary
+	pub fn add(&mut self, path: PathBuf, content: &FileContent) {
+		self.lines += content.lines;
+		self.blank_lines += content.blank_lines;
+		self.comment_lines += content.comment_lines;
+		self.test_lines += content.test_lines;
+		self.files.push(FileSummary {
+			path,
+			lines: content.lines,
+			blank_lines: content.blank_lines,
+			comment_lines: content.comment_lines,
+		});
+	}
+
+	/// Lines which are neither blank nor comments
+	pub fn code_lines(&self) -> usize {
+		self.lines - self.blank_lines - self.comment_lines
+	}
+}
+
+/// Files which weren't recognized as any language, grouped by their extension, or by their
+/// file name if they don't have one
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownSummary {
+	pub name: String,
+	pub file_count: usize,
+	pub lines: usize,
+}
+
+/// Files with exactly the same contents as each other, in order of their paths. Only the
+/// first is counted with `--dedupe`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateGroup {
+	pub paths: Vec<PathBuf>,
+	/// In each copy
+	pub lines: usize,
+}
+
+impl DuplicateGroup {
+	/// The lines in every copy but one
+	pub fn wasted_lines(&self) -> usize {
+		self.lines * (self.paths.len() - 1)
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct CategorySummary {
+	pub category: Category,
+	pub lines: usize,
+	pub blank_lines: usize,
+	pub comment_lines: usize,
+	pub test_lines: usize,
+	pub file_count: usize,
+}
+
+impl CategorySummary {
+	/// Adds up the languages in each category, largest first. The "Other" row doesn't have a
+	/// category, so it isn't counted towards any of them.
+	pub fn from_summaries(summaries: &[LanguageSummary]) -> Vec<Self> {
+		let mut categories: Vec<Self> = vec![];
+		for summary in summaries {
+			let Some(category) = summary.info().category else {
+				continue;
+			};
+			let index = match categories.iter().position(|it| it.category == category) {
+				Some(index) => index,
+				None => {
+					categories.push(Self {
+						category,
+						lines: 0,
+						blank_lines: 0,
+						comment_lines: 0,
+						test_lines: 0,
+						file_count: 0,
+					});
+					categories.len() - 1
+				}
+			};
+			let subtotal = &mut categories[index];
+			subtotal.lines += summary.lines;
+			subtotal.blank_lines += summary.blank_lines;
+			subtotal.comment_lines += summary.comment_lines;
+			subtotal.test_lines += summary.test_lines;
+			subtotal.file_count += summary.files.len();
+		}
+		categories.sort_by_key(|it| std::cmp::Reverse(it.lines));
+		categories
+	}
 }
 
 #[cfg(test)]
@@ -579,7 +1093,7 @@ mod tests {
 
 	#[test]
 	fn language_from_file_name() {
-		let check = |inner| Language::from_file_name(OsStr::new(inner));
+		let check = |inner| Language::detect(OsStr::new(inner)).map(|(language, _)| language);
 
 		assert_eq!(check(""), None);
 		assert_eq!(check("CMakeLists.txt"), Some(CMake));
@@ -589,4 +1103,45 @@ mod tests {
 		assert_eq!(check("README.md"), Some(Markdown));
 		assert_eq!(check("main.zig"), Some(Zig));
 	}
+
+	#[test]
+	fn language_from_shebang() {
+		let check = |line| Language::from_shebang(line).map(|(language, _)| language);
+
+		assert_eq!(check("#!/bin/sh"), Some(Bash));
+		assert_eq!(check("#!/usr/bin/env python3"), Some(Python));
+		assert_eq!(check("#!/usr/bin/env -S python3.12 -u"), Some(Python));
+		assert_eq!(check("#! /usr/local/bin/node --harmony"), Some(JavaScript));
+		assert_eq!(check("#!/usr/bin/env FOO=1 ruby"), Some(Ruby));
+		assert_eq!(check("#!/usr/bin/env"), None);
+		assert_eq!(check("#!/usr/bin/unknown"), None);
+		assert_eq!(check("# not a shebang"), None);
+	}
+
+	#[test]
+	fn category_subtotals() {
+		let summary = |language, lines| LanguageSummary {
+			lines,
+			files: vec![FileSummary::default()],
+			..LanguageSummary::from(language)
+		};
+		let summaries = [
+			summary(Rust, 100),
+			summary(Json, 150),
+			summary(Go, 80),
+			LanguageSummary {
+				lines: 500,
+				..LanguageSummary::other()
+			},
+		];
+
+		let categories = CategorySummary::from_summaries(&summaries)
+			.into_iter()
+			.map(|it| (it.category, it.lines, it.file_count))
+			.collect::<Vec<_>>();
+		assert_eq!(
+			categories,
+			[(Category::Programming, 180, 2), (Category::Data, 150, 1)]
+		);
+	}
 }
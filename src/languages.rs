@@ -0,0 +1,128 @@
+use anyhow::anyhow;
+use colored::Colorize;
+use std::io;
+use std::io::Write;
+
+use crate::langs::Language;
+use crate::reporters::json::string;
+
+/// Handles `kc languages`, which lists every supported language along with all of the ways
+/// that it can be identified, either on the command line or in a file name.
+pub fn list(args: impl IntoIterator<Item = impl AsRef<str>>) -> anyhow::Result<()> {
+	let mut json = false;
+	let mut args = args.into_iter();
+	while let Some(arg) = args.next() {
+		match arg.as_ref() {
+			"-json" | "--json" => json = true,
+			"-O" | "-reporter" | "--reporter" => {
+				let reporter = args
+					.next()
+					.ok_or_else(|| anyhow!("expected a reporter to follow {} flag", arg.as_ref()))?;
+				match reporter.as_ref() {
+					"json" => json = true,
+					"terminal" => json = false,
+					_ => {
+						return Err(anyhow!(
+							"{} flag expects one of \"terminal\", \"json\" when listing languages",
+							arg.as_ref()
+						))
+					}
+				}
+			}
+			arg => return Err(anyhow!("unrecognized option for languages: {}", arg)),
+		}
+	}
+
+	let mut f = io::stdout().lock();
+	if json {
+		list_json(&mut f)
+	} else {
+		list_terminal(&mut f)
+	}
+}
+
+fn json_list(items: impl Iterator<Item = &'static str>) -> String {
+	items.map(string).collect::<Vec<_>>().join(", ")
+}
+
+/// Extensions (with a leading dot), exact file names, and shebang interpreters, for display
+fn patterns(language: Language) -> Vec<String> {
+	language
+		.extensions()
+		.map(|ext| format!(".{ext}"))
+		.chain(language.file_names().map(String::from))
+		.chain(
+			language
+				.interpreters()
+				.map(|program| format!("#!{program}")),
+		)
+		.collect()
+}
+
+fn list_terminal(f: &mut dyn Write) -> anyhow::Result<()> {
+	let rows = Language::ALL
+		.iter()
+		.map(|language| {
+			let info = language.info();
+			let category = info.category.map(|it| it.to_string()).unwrap_or_default();
+			let aliases = language.aliases().collect::<Vec<_>>().join(", ");
+			(info, category, aliases, patterns(*language).join(", "))
+		})
+		.collect::<Vec<_>>();
+
+	let name_width = rows.iter().map(|row| row.0.name.len()).max().unwrap_or(0);
+	let category_width = rows.iter().map(|row| row.1.len()).max().unwrap_or(0);
+	let aliases_width = rows.iter().map(|row| row.2.len()).max().unwrap_or(0);
+
+	writeln!(f)?;
+	for (info, category, aliases, patterns) in rows {
+		writeln!(
+			f,
+			" {}  {:name_width$}  {:category_width$}  {:aliases_width$}  {}",
+			info
+				.color
+				.map(|color| color.color("●"))
+				.unwrap_or_else(|| "●".to_string()),
+			info.name,
+			category.bright_black(),
+			aliases,
+			patterns.bright_black(),
+		)?;
+	}
+	writeln!(f)?;
+
+	Ok(())
+}
+
+fn list_json(f: &mut dyn Write) -> anyhow::Result<()> {
+	writeln!(f, "{{")?;
+	write!(f, "\t\"languages\": [")?;
+	let mut languages = Language::ALL.iter().peekable();
+	while let Some(language) = languages.next() {
+		let info = language.info();
+		write!(
+			f,
+			"\n\t\t{{ \"name\": {}, \"color\": {}, \"category\": {}, \"aliases\": [{}], \"extensions\": [{}], \"file_names\": [{}], \"interpreters\": [{}] }}",
+			string(&info.name),
+			info
+				.color
+				.map(|color| string(&color.hex()))
+				.unwrap_or_else(|| "null".to_string()),
+			info
+				.category
+				.map(|category| string(&category.to_string()))
+				.unwrap_or_else(|| "null".to_string()),
+			json_list(language.aliases()),
+			json_list(language.extensions()),
+			json_list(language.file_names()),
+			json_list(language.interpreters()),
+		)?;
+		if languages.peek().is_some() {
+			write!(f, ",")?;
+		}
+	}
+	writeln!(f, "\n\t]")?;
+	writeln!(f, "}}")?;
+
+	Ok(())
+}
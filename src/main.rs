@@ -2,13 +2,56 @@ use std::env;
 
 mod color;
 mod config;
+mod copy_paste;
+mod explain;
 mod fc;
 mod langs;
+mod languages;
 mod options;
+mod output;
 mod reporters;
 mod scan;
+mod suggest;
 
 fn main() -> anyhow::Result<()> {
-	let options = options::Options::from(env::args().skip(1))?;
-	scan::scan(options)
+	let args = env::args().skip(1).collect::<Vec<_>>();
+
+	// Subcommands take priority over a directory with the same name, which can still be
+	// scanned by writing it as a path, like `./languages`.
+	match args.first().map(String::as_str) {
+		Some("explain") => explain::explain(&args[1..]),
+		Some("languages") => languages::list(&args[1..]),
+		_ => {
+			let options = options::Options::from(&args)?;
+			if options.per_root {
+				for root in &options.roots {
+					let options = options::Options {
+						roots: vec![root.clone()],
+						..options.clone()
+					};
+					run(&options)?;
+				}
+				Ok(())
+			} else {
+				run(&options)
+			}
+		}
+	}
+}
+
+fn run(options: &options::Options) -> anyhow::Result<()> {
+	let scan = scan::scan(options)?;
+	output::report(&scan.summaries, options)?;
+	if options.unknown {
+		output::report_unknown(&scan.unknown, options)?;
+	}
+	if options.duplicates {
+		output::report_duplicates(&scan.duplicates, options)?;
+	}
+	if options.copy_paste {
+		output::report_copy_paste(&scan.copy_paste, options)?;
+	}
+	output::report_skipped(&scan.skipped);
+	output::report_default_ignores(&scan.ignored_by_default, options);
+	output::report_diagnostics(&scan.diagnostics, options)
 }
@@ -5,35 +5,146 @@ use std::ffi::OsStr;
 use std::path::PathBuf;
 use std::process::exit;
 
+use crate::config::default_ignore_names;
+use crate::config::is_default_ignore;
+use crate::langs::Category;
 use crate::langs::Language;
+use crate::reporters::openmetrics::is_valid_label_name;
+use crate::reporters::shields::ShieldsMetric;
+use crate::reporters::svg::SvgStyle;
 use crate::reporters::Reporter;
 use crate::reporters::Reporter::*;
+use crate::reporters::ReporterOutput;
+use crate::scan::SortBy;
+use crate::suggest::did_you_mean;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// The long form of every flag, used to suggest a fix for ones which aren't recognized
+const FLAGS: &[&str] = &[
+	"--version",
+	"--help",
+	"--reporter",
+	"--output",
+	"--template",
+	"--svg-style",
+	"--shields-metric",
+	"--label",
+	"--blame",
+	"--detailed",
+	"--top",
+	"--min-percent",
+	"--sort",
+	"--reverse",
+	"--exclude",
+	"--only",
+	"--category",
+	"--lines",
+	"--unknown",
+	"--verbose",
+	"--strict",
+	"--per-root",
+	"--files-from",
+	"--include",
+	"--exclude-path",
+	"--no-tests",
+	"--default-ignore",
+	"--no-default-ignore",
+	"--follow-symlinks",
+	"--max-depth",
+	"--one-file-system",
+	"--max-filesize",
+	"--ignore-file",
+	"--duplicates",
+	"--dedupe",
+	"--copy-paste",
+	"--min-block-lines",
+];
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct Options {
-	pub root_dir: PathBuf,
-	pub reporter: Reporter,
+	pub roots: Vec<PathBuf>,
+	pub per_root: bool,
+	pub files_from: Option<PathBuf>,
+	/// Globs from `--include` and `--exclude-path`, in the order they were given, written the
+	/// way `ignore`'s overrides expect them, where a leading `!` excludes instead of includes
+	pub path_globs: Vec<String>,
+	pub no_tests: bool,
+	/// Ecosystems or names from `config::DEFAULT_IGNORES` which shouldn't be ignored
+	pub disabled_default_ignores: Vec<String>,
+	/// More names to ignore by default, written the same way as the built in ones
+	pub extra_default_ignores: Vec<String>,
+	pub follow_symlinks: bool,
+	pub max_depth: Option<usize>,
+	pub one_file_system: bool,
+	/// In bytes
+	pub max_filesize: Option<u64>,
+	/// Extra ignore files from `--ignore-file`, on top of the ones found while walking
+	pub ignore_files: Vec<PathBuf>,
+	pub duplicates: bool,
+	pub dedupe: bool,
+	pub copy_paste: bool,
+	/// How many lines of code a block needs to have to be reported by `--copy-paste`
+	pub min_block_lines: usize,
+	pub reporters: Vec<ReporterOutput>,
+	pub output: Option<PathBuf>,
+	pub template: Option<PathBuf>,
+	pub svg_style: SvgStyle,
+	pub shields_metric: ShieldsMetric,
+	pub labels: Vec<(String, String)>,
 	pub include_hidden: bool,
 	pub include_ignored: bool,
 	pub blame: bool,
 	pub detailed: bool,
 	pub head: Option<usize>,
+	pub min_percent: Option<f64>,
+	pub sort_by: SortBy,
+	pub reverse: bool,
 	pub excluded: HashSet<Language>,
 	pub only_include: HashSet<Language>,
+	pub categories: HashSet<Category>,
+	pub unknown: bool,
+	pub verbose: bool,
+	pub strict: bool,
 }
 
 impl Default for Options {
 	fn default() -> Self {
 		Self {
-			root_dir: ".".into(),
-			reporter: Terminal,
+			roots: vec![".".into()],
+			per_root: false,
+			files_from: None,
+			path_globs: Vec::new(),
+			no_tests: false,
+			disabled_default_ignores: Vec::new(),
+			extra_default_ignores: Vec::new(),
+			follow_symlinks: false,
+			max_depth: None,
+			one_file_system: false,
+			max_filesize: None,
+			ignore_files: Vec::new(),
+			duplicates: false,
+			dedupe: false,
+			copy_paste: false,
+			min_block_lines: 6,
+			reporters: vec![Terminal.into()],
+			output: None,
+			template: None,
+			svg_style: SvgStyle::default(),
+			shields_metric: ShieldsMetric::default(),
+			labels: Default::default(),
 			include_hidden: false,
 			include_ignored: false,
 			blame: false,
 			detailed: false,
 			head: None,
+			min_percent: None,
+			sort_by: SortBy::default(),
+			reverse: false,
 			excluded: Default::default(),
 			only_include: Default::default(),
+			categories: Default::default(),
+			unknown: false,
+			verbose: false,
+			strict: false,
 		}
 	}
 }
@@ -42,6 +153,17 @@ impl Options {
 	pub fn from(args: impl IntoIterator<Item = impl AsRef<str>>) -> anyhow::Result<Self> {
 		let mut options = Options::default();
 		let mut args = args.into_iter();
+		// The first reporter specified replaces the default, and any after that are added to it
+		let mut default_reporter = true;
+		let mut add_reporter = |options: &mut Options, output: ReporterOutput| {
+			if default_reporter {
+				options.reporters.clear();
+				default_reporter = false;
+			}
+			options.reporters.push(output);
+		};
+		// The same goes for paths, which replace the current directory
+		let mut default_root = true;
 
 		while let Some(arg) = args.next() {
 			let arg = arg.as_ref();
@@ -49,7 +171,11 @@ impl Options {
 				(arg.len() >= 2 && arg.starts_with('-')) || (arg.len() >= 3 && arg.starts_with("--"));
 
 			if !is_flag {
-				options.root_dir = arg.into();
+				if default_root {
+					options.roots.clear();
+					default_root = false;
+				}
+				options.roots.push(arg.into());
 				continue;
 			}
 
@@ -68,12 +194,75 @@ impl Options {
 					exit(0);
 				}
 				"-O" | "-reporter" | "--reporter" => {
-					options.reporter = args
+					let output = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a reporter to follow {} flag", arg))?;
+					let output = output.as_ref();
+					let output = output.parse::<ReporterOutput>().map_err(|_| {
+						let name = output.split('=').next().unwrap_or_default();
+						anyhow!(
+							"{} flag expects one of {}, optionally followed by =path{}",
+							arg,
+							Reporter::help(),
+							did_you_mean(name, Reporter::names().iter().copied()),
+						)
+					})?;
+					add_reporter(&mut options, output);
+				}
+				"-output" | "--output" => {
+					options.output = Some(
+						args
+							.next()
+							.ok_or_else(|| anyhow!("expected a path to follow {} flag", arg))?
+							.as_ref()
+							.into(),
+					);
+				}
+				"-template" | "--template" => {
+					options.template = Some(
+						args
+							.next()
+							.ok_or_else(|| anyhow!("expected a path to follow {} flag", arg))?
+							.as_ref()
+							.into(),
+					);
+				}
+				"-svg-style" | "--svg-style" => {
+					options.svg_style = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a style to follow {} flag", arg))?
+						.as_ref()
+						.parse::<SvgStyle>()
+						.map_err(|_| anyhow!("{} flag expects one of {}", arg, SvgStyle::help()))?;
+				}
+				"-shields-metric" | "--shields-metric" => {
+					let metric = args
 						.next()
-						.ok_or_else(|| anyhow!("expected a reporter to follow {} flag", arg))?
+						.ok_or_else(|| anyhow!("expected a metric to follow {} flag", arg))?;
+					options.shields_metric = match metric.as_ref() {
+						"lines" => ShieldsMetric::Lines,
+						"files" => ShieldsMetric::Files,
+						lang => ShieldsMetric::Language(
+							parse_language(lang)
+								.map_err(|_| anyhow!("{} flag expects one of {}", arg, ShieldsMetric::help()))?,
+						),
+					};
+				}
+				"-label" | "--label" => {
+					let label = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a name=value label to follow {} flag", arg))?;
+					let (name, value) = label
 						.as_ref()
-						.parse::<Reporter>()
-						.map_err(|_| anyhow!("{} flag expects one of {}", arg, Reporter::help()))?;
+						.split_once('=')
+						.ok_or_else(|| anyhow!("expected a name=value label to follow {} flag", arg))?;
+					if !is_valid_label_name(name) {
+						return Err(anyhow!("\"{}\" can't be used as a label name", name));
+					}
+					if options.labels.iter().any(|(other, _)| other == name) {
+						return Err(anyhow!("the \"{}\" label was given more than once", name));
+					}
+					options.labels.push((name.to_string(), value.to_string()));
 				}
 				"-a" => {
 					options.include_hidden = true;
@@ -85,6 +274,131 @@ impl Options {
 					options.include_hidden = true;
 					options.include_ignored = true;
 				}
+				"-files-from" | "--files-from" => {
+					options.files_from = Some(
+						args
+							.next()
+							.ok_or_else(|| anyhow!("expected a path to follow {} flag", arg))?
+							.as_ref()
+							.into(),
+					);
+				}
+				"-include" | "--include" => {
+					let glob = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a glob to follow {} flag", arg))?;
+					options.path_globs.push(glob.as_ref().to_string());
+				}
+				"-exclude-path" | "--exclude-path" => {
+					let glob = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a glob to follow {} flag", arg))?;
+					let glob = glob.as_ref();
+					// Negating an exclusion is the same as an inclusion
+					options.path_globs.push(match glob.strip_prefix('!') {
+						Some(glob) => glob.to_string(),
+						None => format!("!{}", glob),
+					});
+				}
+				"-default-ignore" | "--default-ignore" => {
+					let names = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a name to follow {} flag", arg))?;
+					for name in names.as_ref().split(',') {
+						options.extra_default_ignores.push(name.to_string());
+					}
+				}
+				"-no-default-ignore" | "--no-default-ignore" => {
+					let names = args
+						.next()
+						.ok_or_else(|| anyhow!("expected an ecosystem or name to follow {} flag", arg))?;
+					for name in names.as_ref().split(',') {
+						if !is_default_ignore(name) {
+							return Err(anyhow!(
+								"{} isn't ignored by default{}",
+								name,
+								did_you_mean(name, default_ignore_names())
+							));
+						}
+						options.disabled_default_ignores.push(name.to_string());
+					}
+				}
+				"-L" | "-follow-symlinks" | "--follow-symlinks" => {
+					options.follow_symlinks = true;
+				}
+				"-max-depth" | "--max-depth" => {
+					let depth = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a number to follow {} flag", arg))?;
+					let depth = depth.as_ref();
+					options.max_depth = Some(
+						depth
+							.parse::<usize>()
+							.map_err(|_| anyhow!("unable to parse \"{}\" as a number", depth))?,
+					);
+				}
+				"-one-file-system" | "--one-file-system" => {
+					options.one_file_system = true;
+				}
+				"-max-filesize" | "--max-filesize" => {
+					let size = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a size to follow {} flag", arg))?;
+					let size = size.as_ref();
+					options.max_filesize = Some(
+						parse_size(size)
+							.ok_or_else(|| anyhow!("unable to parse \"{}\" as a size, like 500K or 2M", size))?,
+					);
+				}
+				"-ignore-file" | "--ignore-file" => {
+					let path = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a path to follow {} flag", arg))?;
+					let path = PathBuf::from(path.as_ref());
+					if !path.is_file() {
+						return Err(anyhow!("ignore file {} does not exist", path.display()));
+					}
+					options.ignore_files.push(path);
+				}
+				"-duplicates" | "--duplicates" => {
+					options.duplicates = true;
+				}
+				"-dedupe" | "--dedupe" => {
+					options.dedupe = true;
+				}
+				"-copy-paste" | "--copy-paste" => {
+					options.copy_paste = true;
+				}
+				"-min-block-lines" | "--min-block-lines" => {
+					let lines = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a number to follow {} flag", arg))?;
+					let lines = lines.as_ref();
+					options.min_block_lines = match lines.parse::<usize>() {
+						Ok(0) | Err(_) => {
+							return Err(anyhow!(
+								"unable to parse \"{}\" as a number of lines",
+								lines
+							))
+						}
+						Ok(lines) => lines,
+					};
+				}
+				"-no-tests" | "--no-tests" => {
+					options.no_tests = true;
+				}
+				"-per-root" | "--per-root" => {
+					options.per_root = true;
+				}
+				"-verbose" | "--verbose" => {
+					options.verbose = true;
+				}
+				"-strict" | "--strict" => {
+					options.strict = true;
+				}
+				"-unknown" | "--unknown" => {
+					options.unknown = true;
+				}
 				"-blame" | "--blame" => {
 					options.blame = true;
 				}
@@ -92,14 +406,43 @@ impl Options {
 					options.detailed = true;
 				}
 				"-h" | "-head" | "--head" | "-t" | "-top" | "--top" => {
-					options.head = args
+					let head = args
 						.next()
-						.ok_or_else(|| anyhow!("expected a number to follow {} flag", arg))?
-						.as_ref()
+						.ok_or_else(|| anyhow!("expected a number to follow {} flag", arg))?;
+					let head = head.as_ref();
+					options.head = head
 						.parse::<usize>()
-						.map_err(|_| anyhow!("unable to parse \"{}\" as a number", arg))?
+						.map_err(|_| anyhow!("unable to parse \"{}\" as a number", head))?
 						.into();
 				}
+				"-min-percent" | "--min-percent" => {
+					let min_percent = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a percentage to follow {} flag", arg))?;
+					let min_percent = min_percent.as_ref();
+					let min_percent = min_percent
+						.trim_end_matches('%')
+						.parse::<f64>()
+						.map_err(|_| anyhow!("unable to parse \"{}\" as a percentage", min_percent))?;
+					if !(0.0..=100.0).contains(&min_percent) {
+						return Err(anyhow!(
+							"{} flag expects a percentage between 0 and 100",
+							arg
+						));
+					}
+					options.min_percent = Some(min_percent);
+				}
+				"-s" | "-sort" | "--sort" => {
+					options.sort_by = args
+						.next()
+						.ok_or_else(|| anyhow!("expected a sort order to follow {} flag", arg))?
+						.as_ref()
+						.parse::<SortBy>()
+						.map_err(|_| anyhow!("{} flag expects one of {}", arg, SortBy::help()))?;
+				}
+				"-r" | "-reverse" | "--reverse" => {
+					options.reverse = true;
+				}
 				"-x" | "-exclude" | "--exclude" | "-ignore" | "--ignore" => {
 					let exclusions = args.next();
 					let list = exclusions
@@ -108,11 +451,7 @@ impl Options {
 						.as_ref()
 						.split(',');
 					for lang in list {
-						options.excluded.insert(
-							Language::from_name(lang)
-								.or_else(|| Language::from_extension(OsStr::new(lang)))
-								.ok_or_else(|| anyhow!("unrecognized language identifier \"{}\"", lang))?,
-						);
+						options.excluded.insert(parse_language(lang)?);
 					}
 				}
 				"-o" | "-only" | "--only" => {
@@ -123,20 +462,36 @@ impl Options {
 						.as_ref()
 						.split(',');
 					for lang in list {
-						options.only_include.insert(
-							Language::from_name(lang)
-								.or_else(|| Language::from_extension(OsStr::new(lang)))
-								.ok_or_else(|| anyhow!("unrecognized language identifier \"{}\"", lang))?,
+						options.only_include.insert(parse_language(lang)?);
+					}
+				}
+				"-c" | "-category" | "--category" => {
+					let categories = args.next();
+					let list = categories
+						.as_ref()
+						.ok_or_else(|| anyhow!("expected a category to follow {} flag", arg))?
+						.as_ref()
+						.split(',');
+					for category in list {
+						options.categories.insert(
+							category
+								.parse::<Category>()
+								.map_err(|_| anyhow!("{} flag expects one of {}", arg, Category::help()))?,
 						);
 					}
 				}
 				"-l" | "-lines" | "--lines" | "-total" | "--total" | "-total-lines" | "--total-lines"
 				| "-totalLines" | "--totalLines" => {
-					options.reporter = TotalLines;
+					add_reporter(&mut options, TotalLines.into());
 				}
 				_ => {
-					eprintln!("unrecognized option: {arg}",);
-					exit(1);
+					// Flags can be written with one or two dashes, so compare them in their long form
+					let long_form = format!("--{}", arg.trim_start_matches('-'));
+					return Err(anyhow!(
+						"unrecognized option: {}{}",
+						arg,
+						did_you_mean(&long_form, FLAGS.iter().copied())
+					));
 				}
 			}
 		}
@@ -145,8 +500,82 @@ impl Options {
 			eprintln!("warning: both --only and --exclude have been set, which doesn't really make sense")
 		}
 
+		let uses_template = options.reporters.iter().any(|it| it.reporter == Template);
+		if options.template.is_some() && !uses_template {
+			add_reporter(&mut options, Template.into());
+		} else if options.template.is_none() && uses_template {
+			return Err(anyhow!("the template reporter requires a --template file"));
+		}
+
+		let mut destinations = HashSet::new();
+		for output in &options.reporters {
+			if let Some(path) = output.path.as_ref().or(options.output.as_ref()) {
+				if !destinations.insert(path) {
+					return Err(anyhow!(
+						"more than one reporter is trying to write to {}",
+						path.display()
+					));
+				}
+			}
+		}
+
+		if options.files_from.is_some() && !default_root {
+			return Err(anyhow!(
+				"--files-from can't be used together with paths to scan"
+			));
+		}
+
+		// Each root gets its own report, so they can't all go to the same file
+		let writes_to_file = options
+			.reporters
+			.iter()
+			.any(|it| it.path.is_some() || options.output.is_some());
+		if options.per_root && options.roots.len() > 1 && writes_to_file {
+			return Err(anyhow!(
+				"--per-root can't be used with reporters that write to files"
+			));
+		}
+
 		Ok(options)
 	}
+
+	/// Describes what was scanned, for reports which mention where their counts came from
+	pub fn root_label(&self) -> String {
+		self
+			.roots
+			.iter()
+			.map(|root| root.display().to_string())
+			.collect::<Vec<_>>()
+			.join(", ")
+	}
+}
+
+/// Parses a number of bytes, with an optional `K`, `M` or `G` suffix for powers of 1024
+fn parse_size(size: &str) -> Option<u64> {
+	let (number, unit) = match size.char_indices().last()? {
+		(i, 'k' | 'K') => (&size[..i], 1 << 10),
+		(i, 'm' | 'M') => (&size[..i], 1 << 20),
+		(i, 'g' | 'G') => (&size[..i], 1 << 30),
+		_ => (size, 1),
+	};
+	number.parse::<u64>().ok()?.checked_mul(unit)
+}
+
+fn parse_language(lang: &str) -> anyhow::Result<Language> {
+	Language::from_name(lang)
+		.or_else(|| Language::from_extension(OsStr::new(lang)))
+		.ok_or_else(|| {
+			let identifiers = Language::ALL
+				.iter()
+				.flat_map(|language| language.aliases().chain(language.extensions()));
+			match did_you_mean(lang, identifiers) {
+				hint if hint.is_empty() => anyhow!(
+					"unrecognized language identifier \"{}\" (run `kc languages` to see them all)",
+					lang
+				),
+				hint => anyhow!("unrecognized language identifier \"{}\"{}", lang, hint),
+			}
+		})
 }
 
 #[cfg(test)]
@@ -154,6 +583,32 @@ mod tests {
 	use super::*;
 	use Language::*;
 
+	#[test]
+	fn suggestions() {
+		let error = |args: &[&str]| Options::from(args).unwrap_err().to_string();
+
+		assert!(error(&["-x", "typscript"]).ends_with("(did you mean \"typescript\"?)"));
+		assert!(error(&["-o", "rsut"]).ends_with("(did you mean \"rust\"?)"));
+		assert!(error(&["--repoter", "json"]).ends_with("(did you mean \"--reporter\"?)"));
+		assert!(error(&["-detialed"]).ends_with("(did you mean \"--detailed\"?)"));
+		assert!(error(&["-O", "markdwn=out.md"]).ends_with("(did you mean \"markdown\"?)"));
+		assert!(error(&["--no-default-ignore", "pyhton"]).ends_with("(did you mean \"python\"?)"));
+	}
+
+	#[test]
+	fn parse_errors() {
+		let error = |args: &[&str]| Options::from(args).unwrap_err().to_string();
+
+		assert_eq!(
+			error(&["--top", "ten"]),
+			"unable to parse \"ten\" as a number"
+		);
+		assert_eq!(
+			error(&["--min-percent", "five"]),
+			"unable to parse \"five\" as a percentage"
+		);
+	}
+
 	#[test]
 	fn from_args() -> anyhow::Result<()> {
 		assert_eq!(
@@ -207,7 +662,81 @@ mod tests {
 		assert_eq!(
 			Options::from(["./test"])?,
 			Options {
-				root_dir: "./test".into(),
+				roots: vec!["./test".into()],
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["src/", "--per-root", "lib/", "build.rs"])?,
+			Options {
+				roots: vec!["src/".into(), "lib/".into(), "build.rs".into()],
+				per_root: true,
+				..Default::default()
+			},
+		);
+
+		assert!(Options::from(["--per-root", "src/", "lib/", "--output", "out.txt"]).is_err());
+
+		assert_eq!(
+			Options::from(["--files-from", "-"])?,
+			Options {
+				files_from: Some("-".into()),
+				..Default::default()
+			},
+		);
+
+		assert!(Options::from(["--files-from", "-", "src/"]).is_err());
+
+		assert_eq!(
+			Options::from([
+				"-L",
+				"--max-depth",
+				"2",
+				"--one-file-system",
+				"--max-filesize",
+				"10K"
+			])?,
+			Options {
+				follow_symlinks: true,
+				max_depth: Some(2),
+				one_file_system: true,
+				max_filesize: Some(10 * 1024),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["--copy-paste", "--min-block-lines", "10"])?,
+			Options {
+				copy_paste: true,
+				min_block_lines: 10,
+				..Default::default()
+			},
+		);
+		assert!(Options::from(["--min-block-lines", "0"]).is_err());
+
+		assert_eq!(parse_size("100"), Some(100));
+		assert_eq!(parse_size("2m"), Some(2 * 1024 * 1024));
+		assert_eq!(parse_size("1G"), Some(1024 * 1024 * 1024));
+		assert_eq!(parse_size("K"), None);
+		assert_eq!(parse_size("-1"), None);
+
+		assert_eq!(
+			Options::from([
+				"--include",
+				"services/*/src/**",
+				"--exclude-path",
+				"**/testdata/**",
+				"--exclude-path",
+				"!**/testdata/keep/**",
+			])?,
+			Options {
+				path_globs: vec![
+					"services/*/src/**".to_string(),
+					"!**/testdata/**".to_string(),
+					"**/testdata/keep/**".to_string(),
+				],
 				..Default::default()
 			},
 		);
@@ -217,7 +746,7 @@ mod tests {
 			Options {
 				excluded: [TypeScript].into(),
 				head: Some(10),
-				root_dir: "./test".into(),
+				roots: vec!["./test".into()],
 				..Default::default()
 			},
 		);
@@ -225,7 +754,7 @@ mod tests {
 		assert_eq!(
 			Options::from(["-l"])?,
 			Options {
-				reporter: TotalLines,
+				reporters: vec![TotalLines.into()],
 				..Default::default()
 			},
 		);
@@ -249,11 +778,148 @@ mod tests {
 		assert_eq!(
 			Options::from(["-O", "html"])?,
 			Options {
-				reporter: Reporter::Html,
+				reporters: vec![Reporter::Html.into()],
 				..Default::default()
 			},
 		);
 
+		assert_eq!(
+			Options::from([
+				"--reporter",
+				"terminal",
+				"-O",
+				"html=report.html",
+				"-O",
+				"json"
+			])?,
+			Options {
+				reporters: vec![
+					Terminal.into(),
+					ReporterOutput {
+						reporter: Reporter::Html,
+						path: Some("report.html".into()),
+					},
+					Reporter::Json.into(),
+				],
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["-l", "--output", "lines.txt"])?,
+			Options {
+				reporters: vec![TotalLines.into()],
+				output: Some("lines.txt".into()),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["--template", "slack.txt"])?,
+			Options {
+				reporters: vec![Template.into()],
+				template: Some("slack.txt".into()),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from([
+				"-O",
+				"template=slack.txt",
+				"--template",
+				"slack.tmpl",
+				"-O",
+				"json"
+			])?,
+			Options {
+				reporters: vec![
+					ReporterOutput {
+						reporter: Template,
+						path: Some("slack.txt".into()),
+					},
+					Reporter::Json.into(),
+				],
+				template: Some("slack.tmpl".into()),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["-O", "svg=badge.svg", "--svg-style", "badge"])?,
+			Options {
+				reporters: vec![ReporterOutput {
+					reporter: Reporter::Svg,
+					path: Some("badge.svg".into()),
+				}],
+				svg_style: SvgStyle::Badge,
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["-O", "shields", "--shields-metric", "ts"])?,
+			Options {
+				reporters: vec![Reporter::Shields.into()],
+				shields_metric: ShieldsMetric::Language(TypeScript),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from([
+				"-O",
+				"openmetrics",
+				"--label",
+				"repo=kc",
+				"--label",
+				"branch=a=b"
+			])?,
+			Options {
+				reporters: vec![Reporter::OpenMetrics.into()],
+				labels: vec![
+					("repo".to_string(), "kc".to_string()),
+					("branch".to_string(), "a=b".to_string()),
+				],
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["--sort", "name", "-r", "-t", "5"])?,
+			Options {
+				sort_by: SortBy::Name,
+				reverse: true,
+				head: Some(5),
+				..Default::default()
+			},
+		);
+
+		assert_eq!(
+			Options::from(["--min-percent", "2.5%"])?,
+			Options {
+				min_percent: Some(2.5),
+				..Default::default()
+			},
+		);
+
+		assert!(Options::from(["--min-percent", "150"]).is_err());
+
+		assert_eq!(
+			Options::from(["--category", "programming,markup"])?,
+			Options {
+				categories: HashSet::from([Category::Programming, Category::Markup]),
+				..Default::default()
+			},
+		);
+
+		assert!(Options::from(["--category", "spreadsheets"]).is_err());
+		assert!(Options::from(["--label", "kind=code"]).is_err());
+		assert!(Options::from(["--label", "team=a", "--label", "team=b"]).is_err());
+		assert!(Options::from(["-O", "template"]).is_err());
+		assert!(Options::from(["-O", "html=out", "-O", "json=out"]).is_err());
+		assert!(Options::from(["-O", "html", "-O", "json", "--output", "out"]).is_err());
+
 		Ok(())
 	}
 }
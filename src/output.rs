@@ -0,0 +1,164 @@
+use anyhow::anyhow;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::process;
+
+use crate::copy_paste::CopyPaste;
+use crate::langs::DuplicateGroup;
+use crate::langs::LanguageSummary;
+use crate::langs::UnknownSummary;
+use crate::options::Options;
+use crate::reporters::copy_paste::CopyPasteReporter;
+use crate::reporters::duplicates::DuplicatesReporter;
+use crate::reporters::unknown::UnknownReporter;
+use crate::reporters::Reporter;
+use crate::reporters::ReporterOutput;
+use crate::scan::Diagnostic;
+
+/// Runs every requested reporter against the same set of summaries, sending each one's
+/// output either to stdout or to its own file.
+pub fn report(summaries: &[LanguageSummary], options: &Options) -> anyhow::Result<()> {
+	for output in &options.reporters {
+		report_one(output, summaries, options)?;
+	}
+
+	Ok(())
+}
+
+/// Lists files that weren't recognized, after everything else. This is meant for finding gaps
+/// in the language table rather than for keeping, so it goes wherever `extra_output` says.
+pub fn report_unknown(unknown: &[UnknownSummary], options: &Options) -> anyhow::Result<()> {
+	let mut f = extra_output(options);
+	UnknownReporter::report(unknown, true, &mut f)?;
+	Ok(f.flush()?)
+}
+
+/// Lists files with the same contents, after everything else, for the same reasons as
+/// `report_unknown`
+pub fn report_duplicates(duplicates: &[DuplicateGroup], options: &Options) -> anyhow::Result<()> {
+	let mut f = extra_output(options);
+	DuplicatesReporter::report(duplicates, true, &mut f)?;
+	Ok(f.flush()?)
+}
+
+/// Lists copy-pasted blocks of code, after everything else, for the same reasons as
+/// `report_unknown`
+pub fn report_copy_paste(copy_paste: &CopyPaste, options: &Options) -> anyhow::Result<()> {
+	let mut f = extra_output(options);
+	CopyPasteReporter::report(copy_paste, true, &mut f)?;
+	Ok(f.flush()?)
+}
+
+/// The extra lists from flags like `--unknown` are only ever plain text, so they follow the
+/// terminal reporter onto stdout, but if stdout has any other report on it, which might be
+/// parsed by another program, then they go to stderr instead, so that they can't corrupt it.
+fn extra_output(options: &Options) -> Box<dyn Write> {
+	let stdout_is_text = options.reporters.iter().all(|output| {
+		output.reporter == Reporter::Terminal || output.path.is_some() || options.output.is_some()
+	});
+	if stdout_is_text {
+		Box::new(io::stdout().lock())
+	} else {
+		Box::new(io::stderr().lock())
+	}
+}
+
+/// Warns about anything that couldn't be read, since it means that the totals are missing
+/// something. Everything goes to stderr, so that it doesn't end up mixed in with a report.
+pub fn report_diagnostics(diagnostics: &[Diagnostic], options: &Options) -> anyhow::Result<()> {
+	if diagnostics.is_empty() {
+		return Ok(());
+	}
+
+	let count = diagnostics.len();
+	let noun = if count == 1 {
+		"file or directory"
+	} else {
+		"files or directories"
+	};
+	if options.verbose {
+		eprintln!("warning: {count} {noun} could not be read:");
+		for diagnostic in diagnostics {
+			eprintln!("  {diagnostic}");
+		}
+	} else {
+		eprintln!("warning: {count} {noun} could not be read (use --verbose to list them)");
+	}
+
+	if options.strict {
+		return Err(anyhow!("{} {} could not be read", count, noun));
+	}
+
+	Ok(())
+}
+
+/// Lists which of the default ignores skipped anything, with `--verbose`, since it can be
+/// surprising when a directory like `vendor/` goes uncounted
+pub fn report_default_ignores(ignored_by_default: &[(String, usize)], options: &Options) {
+	if !options.verbose || ignored_by_default.is_empty() {
+		return;
+	}
+
+	eprintln!("note: some files and directories were ignored by default (use -A or --no-default-ignore to include them):");
+	for (name, count) in ignored_by_default {
+		eprintln!("  {name} ({count} skipped)");
+	}
+}
+
+/// Mentions anything which was left out because of a limit like `--max-depth`, even without
+/// `--verbose`, since those totals are incomplete on purpose
+pub fn report_skipped(skipped: &[(&str, usize)]) {
+	for (reason, count) in skipped {
+		eprintln!("note: skipped {reason} ({count})");
+	}
+}
+
+fn report_one(
+	output: &ReporterOutput,
+	summaries: &[LanguageSummary],
+	options: &Options,
+) -> anyhow::Result<()> {
+	let Some(path) = output.path.as_ref().or(options.output.as_ref()) else {
+		let stdout = io::stdout();
+		let mut stdout = stdout.lock();
+		output.reporter.report(summaries, options, &mut stdout)?;
+		return Ok(stdout.flush()?);
+	};
+
+	// Escape codes don't belong in files, even if stdout happens to be a terminal.
+	colored::control::set_override(false);
+	let mut buffer = Vec::new();
+	let result = output.reporter.report(summaries, options, &mut buffer);
+	colored::control::unset_override();
+	result?;
+
+	write_atomic(path, &buffer)
+		.map_err(|err| anyhow::anyhow!("failed to write {}: {}", path.display(), err))
+}
+
+/// Writes `contents` to a temporary file next to `path`, and then moves it into place, so
+/// that anything watching `path` never sees a partially written report.
+fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+	let file_name = path
+		.file_name()
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "path must have a file name"))?;
+	let mut temp_name = file_name.to_os_string();
+	temp_name.push(format!(".kc-{}.tmp", process::id()));
+	let temp_path = path.with_file_name(temp_name);
+
+	let result = File::create(&temp_path)
+		.and_then(|mut file| {
+			file.write_all(contents)?;
+			file.sync_all()
+		})
+		.and_then(|_| fs::rename(&temp_path, path));
+
+	if result.is_err() {
+		let _ = fs::remove_file(&temp_path);
+	}
+
+	result
+}
@@ -1,14 +1,46 @@
+use colored::Colorize;
+use std::io::Write;
+use std::path::PathBuf;
 use std::str::FromStr;
 
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+
+pub mod copy_paste;
+pub mod duplicates;
 pub mod html;
+pub mod html_interactive;
+pub mod json;
 pub mod markdown;
+pub mod openmetrics;
+pub mod shields;
+pub mod svg;
+pub mod template;
 pub mod terminal;
 pub mod total_lines;
+pub mod unknown;
+
+use html::HtmlReporter;
+use html_interactive::InteractiveHtmlReporter;
+use json::JsonReporter;
+use markdown::MarkdownReporter;
+use openmetrics::OpenMetricsReporter;
+use shields::ShieldsReporter;
+use svg::SvgReporter;
+use template::TemplateReporter;
+use terminal::TerminalReporter;
+use total_lines::TotalLinesReporter;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Reporter {
 	Html,
+	InteractiveHtml,
+	Json,
 	Markdown,
+	OpenMetrics,
+	Shields,
+	Svg,
+	Template,
 	Terminal,
 	TotalLines,
 }
@@ -19,16 +51,163 @@ impl FromStr for Reporter {
 	fn from_str(s: &str) -> Result<Self, Self::Err> {
 		match s.to_ascii_lowercase().as_ref() {
 			"html" => Ok(Self::Html),
+			"html-interactive" | "interactive" => Ok(Self::InteractiveHtml),
+			"json" => Ok(Self::Json),
 			"md" | "markdown" => Ok(Self::Markdown),
+			"openmetrics" | "prometheus" => Ok(Self::OpenMetrics),
+			"shields" => Ok(Self::Shields),
+			"svg" => Ok(Self::Svg),
+			"template" => Ok(Self::Template),
 			"terminal" => Ok(Self::Terminal),
-			"total" | "total_lines" | "total-lines" | "totalLines" => Ok(Self::TotalLines),
+			"total" | "total_lines" | "total-lines" | "totallines" => Ok(Self::TotalLines),
 			_ => Err(()),
 		}
 	}
 }
 
 impl Reporter {
+	/// Every name that can be parsed as a reporter, including aliases
+	pub fn names() -> &'static [&'static str] {
+		&[
+			"html",
+			"html-interactive",
+			"interactive",
+			"json",
+			"md",
+			"markdown",
+			"openmetrics",
+			"prometheus",
+			"shields",
+			"svg",
+			"template",
+			"terminal",
+			"total",
+			"total_lines",
+			"total-lines",
+			"totallines",
+		]
+	}
+
 	pub fn help() -> &'static str {
-		r#""html", "markdown", "terminal", "total-lines""#
+		r#""html", "html-interactive", "json", "markdown", "openmetrics", "shields", "svg", "template", "terminal", "total-lines""#
+	}
+
+	pub fn report(
+		&self,
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		use Reporter::*;
+
+		match self {
+			Html => HtmlReporter::report(summaries, options, f),
+			InteractiveHtml => InteractiveHtmlReporter::report(summaries, options, f),
+			Json => JsonReporter::report(summaries, options, f),
+			Markdown => Ok(write!(f, "{}", MarkdownReporter::new(summaries, options))?),
+			OpenMetrics => OpenMetricsReporter::report(summaries, options, f),
+			Shields => ShieldsReporter::report(summaries, options, f),
+			Svg => SvgReporter::report(summaries, options, f),
+			Template => TemplateReporter::report(summaries, options, f),
+			Terminal => TerminalReporter::report(summaries, options, f),
+			TotalLines => TotalLinesReporter::report(summaries, options, f),
+		}
+	}
+}
+
+/// A reporter, along with where its output should be written. If `path` is `None`, then
+/// the output goes to `--output` if it was given, and to stdout otherwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReporterOutput {
+	pub reporter: Reporter,
+	pub path: Option<PathBuf>,
+}
+
+impl From<Reporter> for ReporterOutput {
+	fn from(reporter: Reporter) -> Self {
+		Self {
+			reporter,
+			path: None,
+		}
+	}
+}
+
+impl FromStr for ReporterOutput {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.split_once('=') {
+			Some((_, "")) => Err(()),
+			Some((reporter, path)) => Ok(Self {
+				reporter: reporter.parse()?,
+				path: Some(path.into()),
+			}),
+			None => Ok(s.parse::<Reporter>()?.into()),
+		}
+	}
+}
+
+/// What percentage of `total` is made up by `lines`, which is 0% of nothing if there aren't any
+/// lines at all, rather than NaN
+pub fn percent(lines: usize, total: usize) -> f64 {
+	match total {
+		0 => 0.0,
+		total => lines as f64 * 100.0 / total as f64,
+	}
+}
+
+/// Bolds the heading of one of the plain text lists, like `--unknown`, unless `color` is off.
+/// Even with it on, `colored` still leaves it plain when the output isn't a terminal.
+pub fn bold(text: &str, color: bool) -> String {
+	match color {
+		true => text.bold().to_string(),
+		false => text.to_string(),
+	}
+}
+
+/// Formats a count in a short, human friendly way, like `950`, `12.3k` or `4.1M`.
+pub fn abbreviate(n: usize) -> String {
+	let (value, suffix) = match n {
+		0..1_000 => return n.to_string(),
+		1_000..1_000_000 => (n as f64 / 1_000.0, "k"),
+		1_000_000..1_000_000_000 => (n as f64 / 1_000_000.0, "M"),
+		_ => (n as f64 / 1_000_000_000.0, "B"),
+	};
+
+	// Only keep a decimal place while there are few enough digits that it's meaningful.
+	let value = if value >= 100.0 {
+		format!("{value:.0}")
+	} else {
+		format!("{value:.1}")
+	};
+	format!("{}{suffix}", value.trim_end_matches(".0"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn reporter_names() {
+		for name in Reporter::names() {
+			assert!(name.parse::<Reporter>().is_ok(), "{name} is not a reporter");
+		}
+	}
+
+	#[test]
+	fn percentages() {
+		assert_eq!(percent(25, 200), 12.5);
+		assert_eq!(percent(0, 0), 0.0);
+	}
+
+	#[test]
+	fn abbreviated_counts() {
+		assert_eq!(abbreviate(0), "0");
+		assert_eq!(abbreviate(999), "999");
+		assert_eq!(abbreviate(1000), "1k");
+		assert_eq!(abbreviate(12_345), "12.3k");
+		assert_eq!(abbreviate(123_456), "123k");
+		assert_eq!(abbreviate(4_100_000), "4.1M");
+		assert_eq!(abbreviate(2_000_000_000), "2B");
 	}
 }
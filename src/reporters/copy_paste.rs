@@ -0,0 +1,136 @@
+use std::io::Write;
+
+use crate::copy_paste::CopyPaste;
+use crate::copy_paste::Location;
+use crate::reporters::bold;
+
+/// How many of the largest fragments to list, since a big repository can have thousands
+const TOP_FRAGMENTS: usize = 10;
+
+pub struct CopyPasteReporter;
+
+impl CopyPasteReporter {
+	pub fn report(copy_paste: &CopyPaste, color: bool, f: &mut dyn Write) -> anyhow::Result<()> {
+		writeln!(f)?;
+		if copy_paste.summaries.is_empty() {
+			writeln!(f, " no copy-pasted code")?;
+			writeln!(f)?;
+			return Ok(());
+		}
+
+		writeln!(f, " {}", bold("copy-pasted code", color))?;
+		let names = copy_paste
+			.summaries
+			.iter()
+			.map(|it| it.language.to_string())
+			.collect::<Vec<_>>();
+		let name_width = names.iter().map(|it| it.len()).max().unwrap_or(0);
+		let copied_width = copy_paste
+			.summaries
+			.iter()
+			.map(|it| it.copied_lines.to_string().len())
+			.max()
+			.unwrap_or(0);
+		for (summary, name) in copy_paste.summaries.iter().zip(&names) {
+			writeln!(
+				f,
+				"  {:name_width$}  {:>copied_width$} of {} lines of code ({:.1}%)",
+				name,
+				summary.copied_lines,
+				summary.code_lines,
+				summary.percent(),
+			)?;
+		}
+
+		writeln!(f)?;
+		writeln!(f, " {}", bold("largest copy-pasted blocks", color))?;
+		let fragments = &copy_paste.fragments[..copy_paste.fragments.len().min(TOP_FRAGMENTS)];
+		let lines_width = fragments
+			.iter()
+			.map(|it| it.code_lines.to_string().len())
+			.max()
+			.unwrap_or(0);
+		for fragment in fragments {
+			let lines = format!("{:>lines_width$} lines", fragment.code_lines);
+			writeln!(f, "  {}  {}", lines, location(&fragment.original))?;
+			writeln!(
+				f,
+				"  {:width$}  {}",
+				"",
+				location(&fragment.copy),
+				width = lines.len()
+			)?;
+		}
+		if copy_paste.fragments.len() > fragments.len() {
+			writeln!(
+				f,
+				"  and {} more",
+				copy_paste.fragments.len() - fragments.len()
+			)?;
+		}
+		writeln!(f)?;
+
+		Ok(())
+	}
+}
+
+fn location(location: &Location) -> String {
+	format!(
+		"{}:{}-{}",
+		location.path.display(),
+		location.start_line,
+		location.end_line
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::copy_paste::CopyPasteSummary;
+	use crate::copy_paste::Fragment;
+	use crate::langs::Language::*;
+
+	#[test]
+	fn columns() {
+		let location = |path: &str, start_line, end_line| Location {
+			path: path.into(),
+			start_line,
+			end_line,
+		};
+		let copy_paste = CopyPaste {
+			summaries: vec![
+				CopyPasteSummary {
+					language: TypeScript,
+					code_lines: 800,
+					copied_lines: 120,
+				},
+				CopyPasteSummary {
+					language: Go,
+					code_lines: 400,
+					copied_lines: 8,
+				},
+			],
+			fragments: vec![
+				Fragment {
+					language: TypeScript,
+					code_lines: 12,
+					original: location("src/a.ts", 10, 24),
+					copy: location("src/b.ts", 40, 53),
+				},
+				Fragment {
+					language: Go,
+					code_lines: 8,
+					original: location("main.go", 3, 10),
+					copy: location("cmd/main.go", 5, 12),
+				},
+			],
+		};
+		let mut output = Vec::new();
+		CopyPasteReporter::report(&copy_paste, false, &mut output).unwrap();
+
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			"\n copy-pasted code\n  TypeScript  120 of 800 lines of code (15.0%)\n  Go            8 of 400 lines of code (2.0%)\n\n largest copy-pasted blocks\n  12 lines  src/a.ts:10-24\n            src/b.ts:40-53\n   8 lines  main.go:3-10\n            cmd/main.go:5-12\n\n"
+		);
+	}
+}
@@ -0,0 +1,80 @@
+use std::io::Write;
+
+use crate::langs::DuplicateGroup;
+use crate::reporters::bold;
+
+pub struct DuplicatesReporter;
+
+impl DuplicatesReporter {
+	pub fn report(
+		duplicates: &[DuplicateGroup],
+		color: bool,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		writeln!(f)?;
+		if duplicates.is_empty() {
+			writeln!(f, " no duplicate files")?;
+			writeln!(f)?;
+			return Ok(());
+		}
+
+		writeln!(f, " {}", bold("duplicate files", color))?;
+		for group in duplicates {
+			writeln!(
+				f,
+				"  {} copies of {} {}, {} wasted",
+				group.paths.len(),
+				group.lines,
+				if group.lines == 1 { "line" } else { "lines" },
+				group.wasted_lines(),
+			)?;
+			for path in &group.paths {
+				writeln!(f, "    {}", path.display())?;
+			}
+		}
+
+		let wasted_lines = duplicates.iter().map(|it| it.wasted_lines()).sum::<usize>();
+		let copies = duplicates
+			.iter()
+			.map(|it| it.paths.len() - 1)
+			.sum::<usize>();
+		writeln!(f)?;
+		writeln!(
+			f,
+			" {} {} in {} extra {}",
+			wasted_lines,
+			if wasted_lines == 1 { "line" } else { "lines" },
+			copies,
+			if copies == 1 { "copy" } else { "copies" },
+		)?;
+		writeln!(f)?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn groups() {
+		let duplicates = [
+			DuplicateGroup {
+				paths: vec!["a/util.js".into(), "b/util.js".into(), "c/util.js".into()],
+				lines: 40,
+			},
+			DuplicateGroup {
+				paths: vec!["LICENSE.md".into(), "vendor/LICENSE.md".into()],
+				lines: 21,
+			},
+		];
+		let mut output = Vec::new();
+		DuplicatesReporter::report(&duplicates, false, &mut output).unwrap();
+
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			"\n duplicate files\n  3 copies of 40 lines, 80 wasted\n    a/util.js\n    b/util.js\n    c/util.js\n  2 copies of 21 lines, 21 wasted\n    LICENSE.md\n    vendor/LICENSE.md\n\n 101 lines in 3 extra copies\n\n"
+		);
+	}
+}
@@ -1,80 +1,289 @@
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Write as _;
+use std::io::Write;
+
 use crate::color::Color;
-use crate::langs::LanguageInfo;
 use crate::langs::LanguageSummary;
 use crate::options::Options;
+use crate::reporters::percent;
+
+/// Writes markup, like `write!`, except that every argument is escaped before it's
+/// interpolated, so anything from the scan (paths, names, colors) is always treated as text.
+/// Only the format string itself is written as-is.
+///
+/// Arguments must be passed positionally, since names captured directly by the format string
+/// (like `{name}`) are formatted by `write!` itself, and can't be escaped. Using one is a
+/// compile error, so that nothing can skip being escaped by accident.
+macro_rules! html {
+	($f:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+		const {
+			assert!(
+				$crate::reporters::html::only_positional($fmt),
+				"html! can only escape positional arguments"
+			)
+		};
+		write!($f, $fmt $(, $crate::reporters::html::Escape(&$arg))*)
+	}};
+}
+
+macro_rules! htmlln {
+	($f:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {{
+		const {
+			assert!(
+				$crate::reporters::html::only_positional($fmt),
+				"htmlln! can only escape positional arguments"
+			)
+		};
+		writeln!($f, $fmt $(, $crate::reporters::html::Escape(&$arg))*)
+	}};
+}
+
+/// Checks that a format string doesn't capture any names, like `{name}`, so that every value
+/// has to be passed as an argument to `html!`, where it gets escaped
+pub const fn only_positional(fmt: &str) -> bool {
+	let bytes = fmt.as_bytes();
+	let mut i = 0;
+	while i < bytes.len() {
+		if bytes[i] == b'{' {
+			if i + 1 < bytes.len() && bytes[i + 1] == b'{' {
+				i += 2;
+				continue;
+			}
+			if i + 1 < bytes.len() && (bytes[i + 1].is_ascii_alphabetic() || bytes[i + 1] == b'_') {
+				return false;
+			}
+		}
+		i += 1;
+	}
+	true
+}
+
+/// Escapes the `Display` output of a value so that it can be safely placed in text or in a
+/// quoted attribute.
+pub struct Escape<T>(pub T);
+
+impl<T: Display> Display for Escape<T> {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		struct Escaper<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+		impl fmt::Write for Escaper<'_, '_> {
+			fn write_str(&mut self, s: &str) -> fmt::Result {
+				for c in s.chars() {
+					match c {
+						'&' => self.0.write_str("&amp;")?,
+						'<' => self.0.write_str("&lt;")?,
+						'>' => self.0.write_str("&gt;")?,
+						'"' => self.0.write_str("&quot;")?,
+						'\'' => self.0.write_str("&#39;")?,
+						c => self.0.write_char(c)?,
+					}
+				}
+				Ok(())
+			}
+		}
+
+		write!(Escaper(f), "{}", self.0)
+	}
+}
 
 pub struct HtmlReporter;
 
 const ROW_STYLES: &str = include_str!("./html_reporter.css");
 
 impl HtmlReporter {
-	pub fn report(summaries: Vec<LanguageSummary>, options: Options) -> anyhow::Result<()> {
-		println!("<!doctype html>");
-		print!(
-			"<html>\n<head>\n<title>{} — kc</title>\n<style>\n{}</style>\n</head>\n",
-			options.root_dir.display(),
-			ROW_STYLES
-		);
-		print!("<body>\n\n");
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		htmlln!(f, "<!doctype html>")?;
+		html!(
+			f,
+			"<html>\n<head>\n<title>{} — kc</title>\n",
+			options.root_label()
+		)?;
+		// The stylesheet is our own, and is the only thing written without escaping.
+		write!(f, "<style>\n{ROW_STYLES}</style>\n</head>\n")?;
+		html!(f, "<body>\n\n")?;
 
 		let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
 		let mut remaining_lines = total_lines;
-		let total_lines = total_lines as f32;
 
-		println!("<div aria-hidden class=\"bar\">");
+		htmlln!(f, "<div aria-hidden class=\"bar\">")?;
 		{
 			for stat in summaries.iter() {
-				// If there are 0 total lines, then just say everything is 0%.
-				let percent = stat.lines as f32 / total_lines;
-				if percent.is_nan() || percent < 0.02 {
+				if percent(stat.lines, total_lines) < 2.0 {
 					break;
 				}
 
 				remaining_lines -= stat.lines;
 
-				let lang = LanguageInfo::from(&stat.language);
+				let lang = stat.info();
 				let color = lang
 					.color
 					.as_ref()
 					.map(Color::hex)
 					.unwrap_or("gray".to_string());
 
-				println!(
+				htmlln!(
+					f,
 					"\t<div aria-hidden title=\"{}\" style=\"background-color: {}; flex-grow: {}\"></div>",
-					lang.name, color, stat.lines,
-				);
+					lang.name,
+					color,
+					stat.lines,
+				)?;
 			}
 
 			if remaining_lines > 0 {
-				println!(
-					"\t<div aria-hidden title=\"Other languages\" style=\"background-color: gray; flex-grow: {remaining_lines}\"></div>",
-				);
+				htmlln!(
+					f,
+					"\t<div aria-hidden title=\"Other languages\" style=\"background-color: gray; flex-grow: {}\"></div>",
+					remaining_lines,
+				)?;
 			}
 		}
-		print!("</div>\n\n");
+		html!(f, "</div>\n\n")?;
 
-		print!(
+		html!(
+			f,
 			"<table>\n\
 			<colgroup><col /><col width=\"15%\" /><col width=\"15%\" /></colgroup>\n\
 			\t<th>Language</th><th>Lines</th><th>Blank</th>\n\n"
-		);
+		)?;
 		{
 			for stat in summaries.iter() {
-				let lang = LanguageInfo::from(&stat.language);
+				let lang = stat.info();
 				let color = lang
 					.color
 					.as_ref()
 					.map(Color::hex)
 					.unwrap_or("gray".to_string());
-				println!(
+				htmlln!(
+					f,
 					"\t<tr><td><span style=\"color: {}\">●</span>&nbsp;{}</td><td>{}</td><td>{}</td></tr>",
-					color, stat.language, stat.lines, stat.blank_lines
-				);
+					color,
+					stat.info().name,
+					stat.lines,
+					stat.blank_lines
+				)?;
+			}
+		}
+		html!(f, "</table>\n\n")?;
+
+		if options.blame {
+			for stat in summaries.iter().filter(|it| !it.files.is_empty()) {
+				let count = stat.files.len();
+				htmlln!(f, "<details>")?;
+				htmlln!(
+					f,
+					"<summary>{} ({} {})</summary>",
+					stat.info().name,
+					count,
+					if count == 1 { "file" } else { "files" }
+				)?;
+				htmlln!(f, "<ul>")?;
+				for file in &stat.files {
+					htmlln!(
+						f,
+						"\t<li><code>{}</code> ({} {})</li>",
+						file.path.display(),
+						file.lines,
+						if file.lines == 1 { "line" } else { "lines" }
+					)?;
+				}
+				html!(f, "</ul>\n</details>\n\n")?;
 			}
 		}
-		print!("</table>\n\n");
 
-		print!("</body>\n</html>\n");
+		html!(f, "</body>\n</html>\n")?;
 		Ok(())
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
+
+	#[test]
+	fn only_positional_arguments() {
+		assert!(only_positional("<td>{}</td><td>{:>5}</td><td>{0}</td>"));
+		assert!(only_positional("{{name}} and {}"));
+		assert!(!only_positional("<td>{name}</td>"));
+		assert!(!only_positional("<td>{_name:?}</td>"));
+	}
+
+	#[test]
+	fn escape() {
+		assert_eq!(
+			Escape(r#"<a href="x">Tom & Jerry's</a>"#).to_string(),
+			"&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+		);
+		assert_eq!(Escape("plain text").to_string(), "plain text");
+	}
+
+	#[test]
+	fn hostile_root_dir() {
+		let summaries = [LanguageSummary {
+			language: Some(Yall),
+			lines: 10,
+			blank_lines: 0,
+			comment_lines: 0,
+			test_lines: 0,
+			files: vec![],
+		}];
+		let options = Options {
+			roots: vec![r#"</title><script>alert("kc")</script>"#.into()],
+			..Default::default()
+		};
+		let mut output = Vec::new();
+		HtmlReporter::report(&summaries, &options, &mut output).unwrap();
+		let output = String::from_utf8(output).unwrap();
+
+		assert!(!output.contains("<script>"));
+		assert!(output.contains(
+			"<title>&lt;/title&gt;&lt;script&gt;alert(&quot;kc&quot;)&lt;/script&gt; — kc</title>"
+		));
+		assert!(output.contains("title=\"Y&#39;all\""));
+		assert!(output.contains("&nbsp;Y&#39;all</td>"));
+	}
+
+	#[test]
+	fn blame() {
+		let summaries = [LanguageSummary {
+			language: Some(Rust),
+			lines: 10,
+			blank_lines: 0,
+			comment_lines: 0,
+			test_lines: 0,
+			files: vec![
+				FileSummary {
+					path: "src/<main>.rs".into(),
+					lines: 9,
+					..Default::default()
+				},
+				FileSummary {
+					path: "src/lib.rs".into(),
+					lines: 1,
+					..Default::default()
+				},
+			],
+		}];
+		let render = |blame| {
+			let options = Options {
+				blame,
+				..Default::default()
+			};
+			let mut output = Vec::new();
+			HtmlReporter::report(&summaries, &options, &mut output).unwrap();
+			String::from_utf8(output).unwrap()
+		};
+
+		assert!(!render(false).contains("<details>"));
+		assert!(render(true).contains(
+			"<details>\n<summary>Rust (2 files)</summary>\n<ul>\n\t<li><code>src/&lt;main&gt;.rs</code> (9 lines)</li>\n\t<li><code>src/lib.rs</code> (1 line)</li>\n</ul>\n</details>\n"
+		));
+	}
+}
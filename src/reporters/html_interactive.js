@@ -0,0 +1,242 @@
+"use strict";
+
+const data = JSON.parse(document.getElementById("kc-data").textContent);
+const GRAY = "#808080";
+
+const totalLines = data.languages.reduce((sum, lang) => sum + lang.lines, 0);
+const totalFiles = data.languages.reduce((sum, lang) => sum + lang.files.length, 0);
+const percent = (lines) => (totalLines ? (lines * 100) / totalLines : 0);
+
+document.title = `${data.root} — kc`;
+document.getElementById("title").textContent = data.root;
+document.getElementById("summary").textContent =
+	`${totalLines.toLocaleString()} lines in ${totalFiles.toLocaleString()} files`;
+
+function el(tag, properties = {}, children = []) {
+	const element = Object.assign(document.createElement(tag), properties);
+	element.append(...children);
+	return element;
+}
+
+// --- Treemap ---------------------------------------------------------------
+
+function buildTree() {
+	const root = { name: data.root, children: new Map(), lines: 0 };
+	for (const lang of data.languages) {
+		for (const [path, lines] of lang.files) {
+			const parts = path.split(/[\\/]/).filter((part) => part && part !== ".");
+			let node = root;
+			node.lines += lines;
+			for (const part of parts.slice(0, -1)) {
+				if (!node.children.has(part)) {
+					node.children.set(part, { name: part, children: new Map(), lines: 0, parent: node });
+				}
+				node = node.children.get(part);
+				node.lines += lines;
+			}
+			const name = parts[parts.length - 1] ?? path;
+			node.children.set(name, { name, path, lines, language: lang, parent: node });
+		}
+	}
+	return root;
+}
+
+// Returns how far the worst aspect ratio in a row strays from a square.
+function worst(row, sum, side, scale) {
+	const area = sum * scale;
+	const max = Math.max(...row.map((it) => it.lines)) * scale;
+	const min = Math.min(...row.map((it) => it.lines)) * scale;
+	return Math.max((side * side * max) / (area * area), (area * area) / (side * side * min));
+}
+
+// The "squarified" layout from Bruls, Huizing, and van Wijk, which keeps tiles close to
+// square so that they're easier to compare and to click.
+function squarify(items, x, y, w, h) {
+	const tiles = [];
+	let remaining = items.filter((it) => it.lines > 0).sort((a, b) => b.lines - a.lines);
+	let total = remaining.reduce((sum, it) => sum + it.lines, 0);
+
+	while (remaining.length && w > 0 && h > 0) {
+		const side = Math.min(w, h);
+		const scale = (w * h) / total;
+		let count = 1;
+		let sum = remaining[0].lines;
+		let ratio = worst(remaining.slice(0, 1), sum, side, scale);
+		while (count < remaining.length) {
+			const next = worst(remaining.slice(0, count + 1), sum + remaining[count].lines, side, scale);
+			if (next > ratio) break;
+			ratio = next;
+			sum += remaining[count].lines;
+			count += 1;
+		}
+
+		const thickness = (sum * scale) / side;
+		let offset = 0;
+		for (const item of remaining.slice(0, count)) {
+			const length = (item.lines * scale) / thickness;
+			tiles.push(
+				w >= h
+					? { item, x, y: y + offset, w: thickness, h: length }
+					: { item, x: x + offset, y, w: length, h: thickness },
+			);
+			offset += length;
+		}
+
+		if (w >= h) {
+			x += thickness;
+			w -= thickness;
+		} else {
+			y += thickness;
+			h -= thickness;
+		}
+		total -= sum;
+		remaining = remaining.slice(count);
+	}
+
+	return tiles;
+}
+
+const treemap = document.getElementById("treemap");
+const breadcrumbs = document.getElementById("breadcrumbs");
+const tree = buildTree();
+
+function drawNode(node, x, y, w, h) {
+	for (const tile of squarify([...node.children.values()], x, y, w, h)) {
+		const { item } = tile;
+		if (tile.w < 2 || tile.h < 2) continue;
+
+		const title = `${item.path ?? item.name} — ${item.lines.toLocaleString()} lines`;
+		const element = el("div", { className: "tile", title });
+		Object.assign(element.style, {
+			left: `${tile.x}px`,
+			top: `${tile.y}px`,
+			width: `${tile.w}px`,
+			height: `${tile.h}px`,
+		});
+
+		if (item.children) {
+			element.classList.add("directory");
+			element.textContent = `${item.name}/`;
+			element.addEventListener("click", (event) => {
+				event.stopPropagation();
+				zoom(item);
+			});
+			treemap.append(element);
+			// Leave room for the directory's name above its contents.
+			drawNode(item, tile.x + 2, tile.y + 16, tile.w - 4, tile.h - 18);
+		} else {
+			element.style.backgroundColor = item.language.color ?? GRAY;
+			element.textContent = item.name;
+			treemap.append(element);
+		}
+	}
+}
+
+let current = tree;
+
+function zoom(node) {
+	current = node;
+	treemap.replaceChildren();
+	drawNode(node, 0, 0, treemap.clientWidth, treemap.clientHeight);
+
+	const trail = [];
+	for (let crumb = node; crumb; crumb = crumb.parent) trail.unshift(crumb);
+	breadcrumbs.replaceChildren(
+		...trail.flatMap((crumb, i) => {
+			const label = i === 0 ? crumb.name : `${crumb.name}/`;
+			const part =
+				crumb === node ? el("span", { textContent: label }) : el("button", { textContent: label });
+			if (crumb !== node) part.addEventListener("click", () => zoom(crumb));
+			return i === 0 ? [part] : [" ", part];
+		}),
+	);
+}
+
+window.addEventListener("resize", () => zoom(current));
+zoom(tree);
+
+// --- Language table --------------------------------------------------------
+
+const columns = [
+	{ label: "Language", key: (lang) => lang.name.toLowerCase() },
+	{ label: "Files", key: (lang) => lang.files.length, number: true },
+	{ label: "Lines", key: (lang) => lang.lines, number: true },
+	{ label: "Comments", key: (lang) => lang.comment_lines, number: true },
+	{ label: "Blank", key: (lang) => lang.blank_lines, number: true },
+	{ label: "%", key: (lang) => percent(lang.lines), number: true },
+];
+
+const table = document.getElementById("languages");
+const search = document.getElementById("search");
+const expanded = new Set();
+let sort = { column: 2, descending: true };
+
+function drawTable() {
+	const query = search.value.trim().toLowerCase();
+	const header = el(
+		"tr",
+		{},
+		columns.map((column, i) => {
+			const th = el("th", { textContent: column.label, className: column.number ? "number" : "" });
+			if (sort.column === i) th.setAttribute("aria-sort", sort.descending ? "descending" : "ascending");
+			th.addEventListener("click", () => {
+				sort = { column: i, descending: sort.column === i ? !sort.descending : !!column.number };
+				drawTable();
+			});
+			return th;
+		}),
+	);
+
+	const { key } = columns[sort.column];
+	const languages = [...data.languages].sort((a, b) => {
+		const order = key(a) < key(b) ? -1 : key(a) > key(b) ? 1 : 0;
+		return sort.descending ? -order : order;
+	});
+
+	const rows = [];
+	for (const lang of languages) {
+		const nameMatches = lang.name.toLowerCase().includes(query);
+		const files = nameMatches
+			? lang.files
+			: lang.files.filter(([path]) => path.toLowerCase().includes(query));
+		if (!nameMatches && files.length === 0) continue;
+
+		const row = el("tr", { className: "language" }, [
+			el("td", {}, [el("span", { className: "swatch" }), lang.name]),
+			el("td", { className: "number", textContent: lang.files.length.toLocaleString() }),
+			el("td", { className: "number", textContent: lang.lines.toLocaleString() }),
+			el("td", { className: "number", textContent: lang.comment_lines.toLocaleString() }),
+			el("td", { className: "number", textContent: lang.blank_lines.toLocaleString() }),
+			el("td", { className: "number", textContent: `${percent(lang.lines).toFixed(1)}%` }),
+		]);
+		row.querySelector(".swatch").style.backgroundColor = lang.color ?? GRAY;
+		row.addEventListener("click", () => {
+			if (expanded.has(lang.name)) expanded.delete(lang.name);
+			else expanded.add(lang.name);
+			drawTable();
+		});
+		rows.push(row);
+
+		// Searching for a file shows it without needing to expand its language first.
+		if (expanded.has(lang.name) || (query && !nameMatches)) {
+			const list = [...files]
+				.sort((a, b) => b[1] - a[1])
+				.map(([path, lines]) =>
+					el("tr", {}, [
+						el("td", { textContent: path }),
+						el("td", { className: "number", textContent: lines.toLocaleString() }),
+					]),
+				);
+			rows.push(
+				el("tr", { className: "files" }, [
+					el("td", { colSpan: columns.length }, [el("table", {}, list)]),
+				]),
+			);
+		}
+	}
+
+	table.replaceChildren(el("thead", {}, [header]), el("tbody", {}, rows));
+}
+
+search.addEventListener("input", drawTable);
+drawTable();
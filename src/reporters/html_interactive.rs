@@ -0,0 +1,152 @@
+use std::io::Write;
+
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+use crate::reporters::json::string;
+
+/// A self-contained HTML report, with a treemap of the scanned directory, and a sortable,
+/// searchable table of languages that expand to show their files.
+///
+/// All of the data is embedded as JSON, and everything visible on the page is built from it
+/// by the script, so nothing from the scan is ever interpreted as markup.
+pub struct InteractiveHtmlReporter;
+
+const STYLES: &str = include_str!("./html_interactive.css");
+const SCRIPT: &str = include_str!("./html_interactive.js");
+
+impl InteractiveHtmlReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		writeln!(f, "<!doctype html>")?;
+		writeln!(f, "<html lang=\"en\">")?;
+		writeln!(f, "<head>")?;
+		writeln!(f, "<meta charset=\"utf-8\" />")?;
+		writeln!(
+			f,
+			"<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\" />"
+		)?;
+		writeln!(f, "<title>kc</title>")?;
+		write!(f, "<style>\n{STYLES}</style>\n")?;
+		writeln!(f, "</head>")?;
+		writeln!(f, "<body>")?;
+		writeln!(
+			f,
+			"<header><h1 id=\"title\"></h1><p id=\"summary\"></p></header>"
+		)?;
+		writeln!(f, "<section>")?;
+		writeln!(f, "\t<nav id=\"breadcrumbs\"></nav>")?;
+		writeln!(f, "\t<div id=\"treemap\"></div>")?;
+		writeln!(f, "</section>")?;
+		writeln!(f, "<section>")?;
+		writeln!(
+			f,
+			"\t<input id=\"search\" type=\"search\" placeholder=\"Filter languages and files\" />"
+		)?;
+		writeln!(f, "\t<table id=\"languages\"></table>")?;
+		writeln!(f, "</section>")?;
+		writeln!(
+			f,
+			"<script type=\"application/json\" id=\"kc-data\">{}</script>",
+			data(summaries, options)
+		)?;
+		write!(f, "<script>\n{SCRIPT}</script>\n")?;
+		writeln!(f, "</body>")?;
+		writeln!(f, "</html>")?;
+
+		Ok(())
+	}
+}
+
+fn data(summaries: &[LanguageSummary], options: &Options) -> String {
+	let languages = summaries
+		.iter()
+		.map(|summary| {
+			let info = summary.info();
+			let files = summary
+				.files
+				.iter()
+				.map(|file| {
+					// The treemap is laid out relative to the root, so trim it off of every path.
+					// With several roots, the paths are kept as they are so that they don't
+					// get mixed together.
+					let path = match options.roots.as_slice() {
+						[root] => file.path.strip_prefix(root).unwrap_or(&file.path),
+						_ => &file.path,
+					};
+					format!(
+						"[{}, {}, {}, {}]",
+						string(&path.display().to_string()),
+						file.lines,
+						file.blank_lines,
+						file.comment_lines
+					)
+				})
+				.collect::<Vec<_>>()
+				.join(", ");
+
+			format!(
+				"{{ \"name\": {}, \"color\": {}, \"lines\": {}, \
+				\"blank_lines\": {}, \"comment_lines\": {}, \"files\": [{}] }}",
+				string(&info.name),
+				info
+					.color
+					.map(|color| string(&color.hex()))
+					.unwrap_or_else(|| "null".to_string()),
+				summary.lines,
+				summary.blank_lines,
+				summary.comment_lines,
+				files,
+			)
+		})
+		.collect::<Vec<_>>()
+		.join(", ");
+
+	let json = format!(
+		"{{ \"root\": {}, \"languages\": [{}] }}",
+		string(&options.root_label()),
+		languages
+	);
+
+	// `<` can only appear inside of strings, where it can be safely replaced with an escape
+	// sequence. This prevents anything like `</script>` from ending the data block early.
+	json.replace('<', "\\u003c")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
+
+	#[test]
+	fn embeds_data_safely() {
+		let summaries = [LanguageSummary {
+			language: Some(Html),
+			lines: 10,
+			blank_lines: 2,
+			comment_lines: 0,
+			test_lines: 0,
+			files: vec![FileSummary {
+				path: "root/</script><script>alert(1)</script>.html".into(),
+				lines: 10,
+				blank_lines: 2,
+				comment_lines: 0,
+			}],
+		}];
+		let options = Options {
+			roots: vec!["root".into()],
+			..Default::default()
+		};
+		let mut output = Vec::new();
+		InteractiveHtmlReporter::report(&summaries, &options, &mut output).unwrap();
+		let output = String::from_utf8(output).unwrap();
+
+		assert_eq!(output.matches("</script>").count(), 2);
+		assert!(
+			output.contains(r#"[["\u003c/script>\u003cscript>alert(1)\u003c/script>.html", 10, 2, 0]]"#)
+		);
+	}
+}
@@ -0,0 +1,129 @@
+use std::fmt::Write as _;
+use std::io::Write;
+
+use crate::langs::CategorySummary;
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+
+pub struct JsonReporter;
+
+impl JsonReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
+		let total_blank_lines = summaries.iter().map(|it| it.blank_lines).sum::<usize>();
+		let total_comment_lines = summaries.iter().map(|it| it.comment_lines).sum::<usize>();
+		let total_test_lines = summaries.iter().map(|it| it.test_lines).sum::<usize>();
+		let total_files = summaries.iter().map(|it| it.files.len()).sum::<usize>();
+
+		writeln!(f, "{{")?;
+		writeln!(f, "\t\"root\": {},", string(&options.root_label()))?;
+		writeln!(
+			f,
+			"\t\"total\": {{ \"lines\": {total_lines}, \"blank_lines\": {total_blank_lines}, \"comment_lines\": {total_comment_lines}, \"test_lines\": {total_test_lines}, \"files\": {total_files} }},"
+		)?;
+		write!(f, "\t\"languages\": [")?;
+
+		let mut languages = summaries.iter().peekable();
+		while let Some(summary) = languages.next() {
+			let info = summary.info();
+			write!(
+				f,
+				"\n\t\t{{ \"language\": {}, \"color\": {}, \"category\": {}, \"lines\": {}, \"blank_lines\": {}, \"comment_lines\": {}, \"test_lines\": {}, \"file_count\": {}",
+				string(&info.name),
+				info
+					.color
+					.map(|color| string(&color.hex()))
+					.unwrap_or_else(|| "null".to_string()),
+				info
+					.category
+					.map(|category| string(&category.to_string()))
+					.unwrap_or_else(|| "null".to_string()),
+				summary.lines,
+				summary.blank_lines,
+				summary.comment_lines,
+				summary.test_lines,
+				summary.files.len(),
+			)?;
+			if options.blame {
+				let files = summary
+					.files
+					.iter()
+					.map(|file| string(&file.path.display().to_string()))
+					.collect::<Vec<_>>()
+					.join(", ");
+				write!(f, ", \"files\": [{files}]")?;
+			}
+			write!(f, " }}")?;
+			if languages.peek().is_some() {
+				write!(f, ",")?;
+			}
+		}
+
+		write!(f, "\n\t]")?;
+
+		if options.detailed {
+			write!(f, ",\n\t\"categories\": [")?;
+			let mut categories = CategorySummary::from_summaries(summaries)
+				.into_iter()
+				.peekable();
+			while let Some(category) = categories.next() {
+				write!(
+					f,
+					"\n\t\t{{ \"category\": {}, \"lines\": {}, \"blank_lines\": {}, \"comment_lines\": {}, \"test_lines\": {}, \"file_count\": {} }}",
+					string(&category.category.to_string()),
+					category.lines,
+					category.blank_lines,
+					category.comment_lines,
+					category.test_lines,
+					category.file_count,
+				)?;
+				if categories.peek().is_some() {
+					write!(f, ",")?;
+				}
+			}
+			write!(f, "\n\t]")?;
+		}
+
+		writeln!(f)?;
+		writeln!(f, "}}")?;
+
+		Ok(())
+	}
+}
+
+/// Formats `s` as a quoted JSON string literal.
+pub fn string(s: &str) -> String {
+	let mut out = String::with_capacity(s.len() + 2);
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => {
+				let _ = write!(out, "\\u{:04x}", c as u32);
+			}
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+	out
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escapes_strings() {
+		assert_eq!(string("hello"), r#""hello""#);
+		assert_eq!(string(r#"C:\"quoted""#), r#""C:\\\"quoted\"""#);
+		assert_eq!(string("a\nb\u{1}"), r#""a\nb\u0001""#);
+	}
+}
@@ -1,78 +1,226 @@
+use crate::langs::CategorySummary;
 use crate::langs::LanguageSummary;
 use crate::options::Options;
+use crate::reporters::percent;
 use std::fmt;
 use std::fmt::Display;
 
-pub struct MarkdownReporter {
-	summaries: Vec<LanguageSummary>,
-	options: Options,
+pub struct MarkdownReporter<'a> {
+	summaries: &'a [LanguageSummary],
+	options: &'a Options,
 }
 
-impl MarkdownReporter {
-	pub fn new(summaries: Vec<LanguageSummary>, options: Options) -> Self {
+impl<'a> MarkdownReporter<'a> {
+	pub fn new(summaries: &'a [LanguageSummary], options: &'a Options) -> Self {
 		MarkdownReporter { summaries, options }
 	}
 }
 
-impl Display for MarkdownReporter {
+impl Display for MarkdownReporter<'_> {
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-		let dir_path = &self.options.root_dir;
 		if self.summaries.is_empty() {
-			writeln!(f, "no code found in {}", dir_path.display())?;
+			writeln!(f, "no code found in {}", self.options.root_label())?;
 			return Ok(());
 		}
 
-		let mut longest_language_name = "Language".len();
-		let mut longest_line_count = "Lines".len();
-
-		let summaries = self
+		let total = self
+			.summaries
+			.iter()
+			.fold(SerializedSummary::default(), |mut total, it| {
+				total.lines += it.lines;
+				total.blank_lines += it.blank_lines;
+				total.comment_lines += it.comment_lines;
+				total.test_lines += it.test_lines;
+				total.files += it.files.len();
+				total
+			});
+		let rows = self
 			.summaries
 			.iter()
 			.map(|it| {
-				let summary = SerializedSummary {
-					language_name: it.language.to_string(),
-					lines: it.lines.to_string(),
-				};
-				longest_language_name = summary.language_name.len().max(longest_language_name);
-				longest_line_count = summary.lines.len().max(longest_line_count);
-				summary
+				(
+					it.info().name,
+					SerializedSummary {
+						lines: it.lines,
+						blank_lines: it.blank_lines,
+						comment_lines: it.comment_lines,
+						test_lines: it.test_lines,
+						files: it.files.len(),
+					},
+				)
 			})
-			.collect::<Vec<_>>();
-
-		writeln!(
-			f,
-			"| {:<lang_width$} | {:<line_count_width$} |",
-			"Language",
-			"Lines",
-			lang_width = longest_language_name,
-			line_count_width = longest_line_count,
-		)?;
-		let language_dashes = "-".repeat(longest_language_name);
-		let lines_dashes = "-".repeat(longest_line_count);
-		writeln!(f, "|-{language_dashes}-|-{lines_dashes}-|")?;
-		for summary in summaries {
-			writeln!(
-				f,
-				"| {:<lang_width$} | {:>line_count_width$} |",
-				summary.language_name,
-				summary.lines,
-				lang_width = longest_language_name,
-				line_count_width = longest_line_count,
-			)?;
+			.chain([("**Total**".to_string(), total)]);
+		self.table(f, "Language", rows, total.lines)?;
+
+		if self.options.detailed {
+			let rows = CategorySummary::from_summaries(self.summaries)
+				.into_iter()
+				.map(|it| {
+					(
+						it.category.to_string(),
+						SerializedSummary {
+							lines: it.lines,
+							blank_lines: it.blank_lines,
+							comment_lines: it.comment_lines,
+							test_lines: it.test_lines,
+							files: it.file_count,
+						},
+					)
+				});
+			writeln!(f)?;
+			self.table(f, "Category", rows, total.lines)?;
+		}
+
+		if self.options.blame {
+			for summary in self.summaries {
+				if summary.files.is_empty() {
+					continue;
+				}
+
+				let count = summary.files.len();
+				writeln!(f)?;
+				writeln!(f, "<details>")?;
+				writeln!(
+					f,
+					"<summary>{} ({} {})</summary>",
+					summary.info().name,
+					count,
+					if count == 1 { "file" } else { "files" }
+				)?;
+				writeln!(f)?;
+				for file in &summary.files {
+					writeln!(
+						f,
+						"- {} ({} {})",
+						code(&file.path.display().to_string()),
+						file.lines,
+						if file.lines == 1 { "line" } else { "lines" }
+					)?;
+				}
+				writeln!(f)?;
+				writeln!(f, "</details>")?;
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl MarkdownReporter<'_> {
+	fn table(
+		&self,
+		f: &mut fmt::Formatter,
+		header: &'static str,
+		rows: impl Iterator<Item = (String, SerializedSummary)>,
+		total_lines: usize,
+	) -> fmt::Result {
+		let share = |lines: usize| format!("{:.1}%", percent(lines, total_lines));
+
+		let mut columns = vec![Column::new(header, false), Column::new("Lines", true)];
+		if self.options.detailed {
+			columns.push(Column::new("Blank", true));
+			columns.push(Column::new("Comments", true));
+			columns.push(Column::new("Tests", true));
+			columns.push(Column::new("Files", true));
+			columns.push(Column::new("%", true));
+		}
+
+		let mut row_count = 0;
+		for (name, summary) in rows {
+			let mut cells = vec![name, summary.lines.to_string()];
+			if self.options.detailed {
+				cells.push(summary.blank_lines.to_string());
+				cells.push(summary.comment_lines.to_string());
+				cells.push(summary.test_lines.to_string());
+				cells.push(summary.files.to_string());
+				cells.push(share(summary.lines));
+			}
+			for (column, cell) in columns.iter_mut().zip(cells) {
+				column.push(cell);
+			}
+			row_count += 1;
+		}
+
+		for column in &columns {
+			write!(f, "| {:<width$} ", column.header, width = column.width)?;
+		}
+		writeln!(f, "|")?;
+		for column in &columns {
+			// GFM alignment markers, so that numbers line up when rendered, too
+			let dashes = "-".repeat(column.width);
+			let end = if column.numeric { ':' } else { '-' };
+			write!(f, "|-{dashes}{end}")?;
+		}
+		writeln!(f, "|")?;
+		for row in 0..row_count {
+			for column in &columns {
+				if column.numeric {
+					write!(f, "| {:>width$} ", column.cells[row], width = column.width)?;
+				} else {
+					write!(f, "| {:<width$} ", column.cells[row], width = column.width)?;
+				}
+			}
+			writeln!(f, "|")?;
 		}
 
 		Ok(())
 	}
 }
 
+#[derive(Clone, Copy, Default)]
 struct SerializedSummary {
-	language_name: String,
-	lines: String,
+	lines: usize,
+	blank_lines: usize,
+	comment_lines: usize,
+	test_lines: usize,
+	files: usize,
+}
+
+struct Column {
+	header: &'static str,
+	numeric: bool,
+	width: usize,
+	cells: Vec<String>,
+}
+
+impl Column {
+	fn new(header: &'static str, numeric: bool) -> Self {
+		Column {
+			header,
+			numeric,
+			width: header.len(),
+			cells: vec![],
+		}
+	}
+
+	fn push(&mut self, cell: String) {
+		self.width = self.width.max(cell.chars().count());
+		self.cells.push(cell);
+	}
+}
+
+/// Wraps text in a code span, using a fence which is longer than any run of backticks in the
+/// text, so that paths are never interpreted as markdown.
+fn code(text: &str) -> String {
+	let longest_run = text
+		.split(|c| c != '`')
+		.map(|run| run.len())
+		.max()
+		.unwrap_or(0);
+	let fence = "`".repeat(longest_run + 1);
+	let padding = if text.starts_with('`') || text.ends_with('`') {
+		" "
+	} else {
+		""
+	};
+	format!("{fence}{padding}{text}{padding}{fence}")
 }
 
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
 
 	#[test]
 	fn column_sizing() {
@@ -80,13 +228,55 @@ mod tests {
 		let output = format!(
 			"{}",
 			MarkdownReporter::new(
-				vec![LanguageSummary {
-					language: crate::langs::Language::TypeScript,
+				&[LanguageSummary {
+					language: Some(TypeScript),
 					lines: 113997,
 					blank_lines: 0,
+					comment_lines: 0,
+					test_lines: 0,
 					files: vec![],
 				}],
-				Options::default(),
+				&Options::default(),
+			)
+		);
+
+		assert_eq!(output, expected);
+	}
+
+	#[test]
+	fn detailed_and_blame() {
+		let expected = include_str!("./testdata/markdown_detailed_output.md");
+		let file = |path: &str, lines| FileSummary {
+			path: path.into(),
+			lines,
+			..Default::default()
+		};
+		let output = format!(
+			"{}",
+			MarkdownReporter::new(
+				&[
+					LanguageSummary {
+						language: Some(Rust),
+						lines: 300,
+						blank_lines: 30,
+						comment_lines: 12,
+						test_lines: 80,
+						files: vec![file("src/main.rs", 299), file("src/`odd`.rs", 1)],
+					},
+					LanguageSummary {
+						language: Some(Toml),
+						lines: 100,
+						blank_lines: 4,
+						comment_lines: 0,
+						test_lines: 0,
+						files: vec![file("Cargo.toml", 100)],
+					},
+				],
+				&Options {
+					detailed: true,
+					blame: true,
+					..Default::default()
+				},
 			)
 		);
 
@@ -0,0 +1,144 @@
+use std::io::Write;
+
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+
+/// Emits gauges in the OpenMetrics text format, which is also understood by the Prometheus
+/// node exporter's textfile collector.
+pub struct OpenMetricsReporter;
+
+impl OpenMetricsReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let extra_labels = options
+			.labels
+			.iter()
+			.map(|(name, value)| format!(",{}=\"{}\"", name, escape(value)))
+			.collect::<String>();
+
+		writeln!(f, "# HELP kc_lines Number of lines, by language and kind.")?;
+		writeln!(f, "# TYPE kc_lines gauge")?;
+		for summary in summaries {
+			let language = escape(&summary.info().name);
+			writeln!(
+				f,
+				"kc_lines{{language=\"{language}\",kind=\"code\"{extra_labels}}} {}",
+				summary.code_lines()
+			)?;
+			writeln!(
+				f,
+				"kc_lines{{language=\"{language}\",kind=\"comment\"{extra_labels}}} {}",
+				summary.comment_lines
+			)?;
+			writeln!(
+				f,
+				"kc_lines{{language=\"{language}\",kind=\"blank\"{extra_labels}}} {}",
+				summary.blank_lines
+			)?;
+		}
+
+		writeln!(
+			f,
+			"# HELP kc_test_lines Number of lines which belong to tests, by language."
+		)?;
+		writeln!(f, "# TYPE kc_test_lines gauge")?;
+		for summary in summaries {
+			let language = escape(&summary.info().name);
+			writeln!(
+				f,
+				"kc_test_lines{{language=\"{language}\"{extra_labels}}} {}",
+				summary.test_lines
+			)?;
+		}
+
+		writeln!(f, "# HELP kc_files Number of files, by language.")?;
+		writeln!(f, "# TYPE kc_files gauge")?;
+		for summary in summaries {
+			writeln!(
+				f,
+				"kc_files{{language=\"{}\"{extra_labels}}} {}",
+				escape(&summary.info().name),
+				summary.files.len()
+			)?;
+		}
+
+		writeln!(f, "# EOF")?;
+		Ok(())
+	}
+}
+
+/// Label names must look like identifiers, and can't start with `__`, which is reserved.
+pub fn is_valid_label_name(name: &str) -> bool {
+	let mut chars = name.chars();
+	chars
+		.next()
+		.is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+		&& !name.starts_with("__")
+		&& !["language", "kind"].contains(&name)
+}
+
+fn escape(value: &str) -> String {
+	value
+		.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
+
+	#[test]
+	fn gauges() {
+		let summaries = [LanguageSummary {
+			language: Some(Rust),
+			lines: 120,
+			blank_lines: 20,
+			comment_lines: 5,
+			test_lines: 40,
+			files: vec![FileSummary::named("main.rs"), FileSummary::named("lib.rs")],
+		}];
+		let options = Options {
+			labels: vec![
+				("repo".into(), "kc".into()),
+				("branch".into(), "feat/\"quoted\"".into()),
+			],
+			..Default::default()
+		};
+		let mut output = Vec::new();
+		OpenMetricsReporter::report(&summaries, &options, &mut output).unwrap();
+
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			"# HELP kc_lines Number of lines, by language and kind.\n\
+			# TYPE kc_lines gauge\n\
+			kc_lines{language=\"Rust\",kind=\"code\",repo=\"kc\",branch=\"feat/\\\"quoted\\\"\"} 95\n\
+			kc_lines{language=\"Rust\",kind=\"comment\",repo=\"kc\",branch=\"feat/\\\"quoted\\\"\"} 5\n\
+			kc_lines{language=\"Rust\",kind=\"blank\",repo=\"kc\",branch=\"feat/\\\"quoted\\\"\"} 20\n\
+			# HELP kc_test_lines Number of lines which belong to tests, by language.\n\
+			# TYPE kc_test_lines gauge\n\
+			kc_test_lines{language=\"Rust\",repo=\"kc\",branch=\"feat/\\\"quoted\\\"\"} 40\n\
+			# HELP kc_files Number of files, by language.\n\
+			# TYPE kc_files gauge\n\
+			kc_files{language=\"Rust\",repo=\"kc\",branch=\"feat/\\\"quoted\\\"\"} 2\n\
+			# EOF\n"
+		);
+	}
+
+	#[test]
+	fn label_names() {
+		assert!(is_valid_label_name("repo"));
+		assert!(is_valid_label_name("_branch2"));
+		assert!(!is_valid_label_name(""));
+		assert!(!is_valid_label_name("2fast"));
+		assert!(!is_valid_label_name("kebab-case"));
+		assert!(!is_valid_label_name("__reserved"));
+		assert!(!is_valid_label_name("language"));
+	}
+}
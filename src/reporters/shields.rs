@@ -0,0 +1,126 @@
+use std::io::Write;
+
+use crate::langs::Language;
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+use crate::reporters::abbreviate;
+use crate::reporters::json::string;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ShieldsMetric {
+	#[default]
+	Lines,
+	Files,
+	Language(Language),
+}
+
+impl ShieldsMetric {
+	pub fn help() -> &'static str {
+		r#""lines", "files", or a language"#
+	}
+}
+
+/// Emits a badge description for the shields.io "endpoint" badge
+/// (https://shields.io/badges/endpoint-badge)
+pub struct ShieldsReporter;
+
+impl ShieldsReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let top_language = summaries.iter().max_by_key(|it| it.lines);
+		let (label, count, language) = match options.shields_metric {
+			ShieldsMetric::Lines => (
+				"lines of code".to_string(),
+				summaries.iter().map(|it| it.lines).sum(),
+				top_language.and_then(|it| it.language),
+			),
+			ShieldsMetric::Files => (
+				"files".to_string(),
+				summaries.iter().map(|it| it.files.len()).sum(),
+				top_language.and_then(|it| it.language),
+			),
+			ShieldsMetric::Language(language) => (
+				language.info().name,
+				summaries
+					.iter()
+					.find(|it| it.language == Some(language))
+					.map(|it| it.lines)
+					.unwrap_or(0),
+				Some(language),
+			),
+		};
+
+		let color = language
+			.and_then(|it| it.info().color)
+			.map(|color| color.hex().trim_start_matches('#').to_string())
+			.unwrap_or_else(|| "lightgrey".to_string());
+
+		writeln!(
+			f,
+			"{{ \"schemaVersion\": 1, \"label\": {}, \"message\": {}, \"color\": {} }}",
+			string(&label),
+			string(&abbreviate(count)),
+			string(&color),
+		)?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
+
+	fn render(shields_metric: ShieldsMetric) -> String {
+		let summaries = [
+			LanguageSummary {
+				language: Some(Toml),
+				lines: 200,
+				blank_lines: 0,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![FileSummary::named("Cargo.toml")],
+			},
+			LanguageSummary {
+				language: Some(Rust),
+				lines: 12_345,
+				blank_lines: 0,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![FileSummary::named("main.rs"), FileSummary::named("lib.rs")],
+			},
+		];
+		let options = Options {
+			shields_metric,
+			..Default::default()
+		};
+		let mut output = Vec::new();
+		ShieldsReporter::report(&summaries, &options, &mut output).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+
+	#[test]
+	fn metrics() {
+		assert_eq!(
+			render(ShieldsMetric::Lines),
+			"{ \"schemaVersion\": 1, \"label\": \"lines of code\", \"message\": \"12.5k\", \"color\": \"a72145\" }\n"
+		);
+		assert_eq!(
+			render(ShieldsMetric::Files),
+			"{ \"schemaVersion\": 1, \"label\": \"files\", \"message\": \"3\", \"color\": \"a72145\" }\n"
+		);
+		assert_eq!(
+			render(ShieldsMetric::Language(Toml)),
+			"{ \"schemaVersion\": 1, \"label\": \"TOML\", \"message\": \"200\", \"color\": \"lightgrey\" }\n"
+		);
+		assert_eq!(
+			render(ShieldsMetric::Language(Go)),
+			"{ \"schemaVersion\": 1, \"label\": \"Go\", \"message\": \"0\", \"color\": \"00add8\" }\n"
+		);
+	}
+}
@@ -0,0 +1,314 @@
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::io::Write;
+use std::str::FromStr;
+
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+use crate::reporters::abbreviate;
+use crate::reporters::html::Escape;
+use crate::reporters::percent;
+
+const WIDTH: usize = 600;
+const BAR_HEIGHT: usize = 8;
+const LEGEND_COLUMNS: usize = 3;
+const LEGEND_ROW_HEIGHT: usize = 20;
+const GRAY: &str = "#808080";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SvgStyle {
+	#[default]
+	Bar,
+	Legend,
+	Badge,
+}
+
+impl FromStr for SvgStyle {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_ref() {
+			"bar" => Ok(Self::Bar),
+			"legend" => Ok(Self::Legend),
+			"badge" => Ok(Self::Badge),
+			_ => Err(()),
+		}
+	}
+}
+
+impl SvgStyle {
+	pub fn help() -> &'static str {
+		r#""bar", "legend", "badge""#
+	}
+}
+
+pub struct SvgReporter;
+
+impl SvgReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let id = id(summaries, options.svg_style);
+		match options.svg_style {
+			SvgStyle::Bar => Self::bar(summaries, false, &id, f),
+			SvgStyle::Legend => Self::bar(summaries, true, &id, f),
+			SvgStyle::Badge => Self::badge(summaries, &id, f),
+		}
+	}
+
+	fn bar(
+		summaries: &[LanguageSummary],
+		legend: bool,
+		id: &str,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
+		let rows = if legend {
+			summaries.len().div_ceil(LEGEND_COLUMNS)
+		} else {
+			0
+		};
+		let height = BAR_HEIGHT + rows * LEGEND_ROW_HEIGHT + if rows > 0 { 8 } else { 0 };
+
+		writeln!(
+			f,
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{height}\" viewBox=\"0 0 {WIDTH} {height}\">"
+		)?;
+		writeln!(f, "<clipPath id=\"{id}-bar\">")?;
+		writeln!(
+			f,
+			"\t<rect width=\"{WIDTH}\" height=\"{BAR_HEIGHT}\" rx=\"{}\" />",
+			BAR_HEIGHT / 2
+		)?;
+		writeln!(f, "</clipPath>")?;
+		writeln!(f, "<g clip-path=\"url(#{id}-bar)\">")?;
+		writeln!(
+			f,
+			"\t<rect width=\"{WIDTH}\" height=\"{BAR_HEIGHT}\" fill=\"{GRAY}\" />"
+		)?;
+
+		// Round the running total rather than each segment, so that the segments always add up
+		// to the full width of the bar.
+		let mut filled = 0;
+		let mut x = 0;
+		for summary in summaries.iter() {
+			filled += summary.lines;
+			// With nothing to count, the bar is left empty
+			let end = (filled * WIDTH).checked_div(total_lines).unwrap_or(0);
+			let width = end - x;
+			if width > 0 {
+				let info = summary.info();
+				writeln!(
+					f,
+					"\t<rect x=\"{x}\" width=\"{width}\" height=\"{BAR_HEIGHT}\" fill=\"{}\"><title>{}</title></rect>",
+					color(summary),
+					Escape(&info.name),
+				)?;
+			}
+			x = end;
+		}
+		writeln!(f, "</g>")?;
+
+		if legend {
+			writeln!(
+				f,
+				"<g font-family=\"-apple-system, BlinkMacSystemFont, 'Segoe UI', Helvetica, Arial, sans-serif\" font-size=\"12\">"
+			)?;
+			let column_width = WIDTH / LEGEND_COLUMNS;
+			for (i, summary) in summaries.iter().enumerate() {
+				let x = (i % LEGEND_COLUMNS) * column_width;
+				let y = BAR_HEIGHT + 8 + (i / LEGEND_COLUMNS) * LEGEND_ROW_HEIGHT;
+				let percent = percent(summary.lines, total_lines);
+				writeln!(
+					f,
+					"\t<circle cx=\"{}\" cy=\"{}\" r=\"5\" fill=\"{}\" />",
+					x + 5,
+					y + 10,
+					color(summary),
+				)?;
+				writeln!(
+					f,
+					"\t<text x=\"{}\" y=\"{}\" fill=\"#24292f\">{} <tspan fill=\"#57606a\">{:.1}%</tspan></text>",
+					x + 16,
+					y + 14,
+					Escape(&summary.info().name),
+					percent,
+				)?;
+			}
+			writeln!(f, "</g>")?;
+		}
+
+		writeln!(f, "</svg>")?;
+		Ok(())
+	}
+
+	fn badge(summaries: &[LanguageSummary], id: &str, f: &mut dyn Write) -> anyhow::Result<()> {
+		let label = "lines of code";
+		let message = abbreviate(summaries.iter().map(|it| it.lines).sum());
+		let message_color = summaries
+			.iter()
+			.max_by_key(|it| it.lines)
+			.map(color)
+			.unwrap_or_else(|| GRAY.to_string());
+
+		// There's no way to measure text without a font, so estimate it the way most badge
+		// generators do, with an average character width for 11px Verdana.
+		let label_width = text_width(label) + 10;
+		let message_width = text_width(&message) + 10;
+		let width = label_width + message_width;
+
+		writeln!(
+			f,
+			"<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"20\" role=\"img\" aria-label=\"{label}: {message}\">"
+		)?;
+		writeln!(f, "<title>{label}: {message}</title>")?;
+		writeln!(f, "<linearGradient id=\"{id}-s\" x2=\"0\" y2=\"100%\">")?;
+		writeln!(
+			f,
+			"\t<stop offset=\"0\" stop-color=\"#bbb\" stop-opacity=\".1\" />"
+		)?;
+		writeln!(f, "\t<stop offset=\"1\" stop-opacity=\".1\" />")?;
+		writeln!(f, "</linearGradient>")?;
+		writeln!(f, "<clipPath id=\"{id}-r\">")?;
+		writeln!(
+			f,
+			"\t<rect width=\"{width}\" height=\"20\" rx=\"3\" fill=\"#fff\" />"
+		)?;
+		writeln!(f, "</clipPath>")?;
+		writeln!(f, "<g clip-path=\"url(#{id}-r)\">")?;
+		writeln!(
+			f,
+			"\t<rect width=\"{label_width}\" height=\"20\" fill=\"#555\" />"
+		)?;
+		writeln!(
+			f,
+			"\t<rect x=\"{label_width}\" width=\"{message_width}\" height=\"20\" fill=\"{message_color}\" />"
+		)?;
+		writeln!(
+			f,
+			"\t<rect width=\"{width}\" height=\"20\" fill=\"url(#{id}-s)\" />"
+		)?;
+		writeln!(f, "</g>")?;
+		writeln!(
+			f,
+			"<g fill=\"#fff\" text-anchor=\"middle\" font-family=\"Verdana, Geneva, DejaVu Sans, sans-serif\" font-size=\"11\">"
+		)?;
+		writeln!(
+			f,
+			"\t<text x=\"{}\" y=\"14\">{label}</text>",
+			label_width / 2
+		)?;
+		writeln!(
+			f,
+			"\t<text x=\"{}\" y=\"14\">{message}</text>",
+			label_width + message_width / 2
+		)?;
+		writeln!(f, "</g>")?;
+		writeln!(f, "</svg>")?;
+
+		Ok(())
+	}
+}
+
+/// Ids are shared by everything on a page, so several images inlined into the same page, like
+/// badges in a README, would otherwise refer to each other's gradients and clip paths. Deriving
+/// them from what's drawn keeps them apart, while keeping the output the same from run to run.
+fn id(summaries: &[LanguageSummary], svg_style: SvgStyle) -> String {
+	let mut hasher = DefaultHasher::new();
+	svg_style.hash(&mut hasher);
+	for summary in summaries {
+		summary.language.hash(&mut hasher);
+		summary.lines.hash(&mut hasher);
+	}
+	format!("kc-{:08x}", hasher.finish() as u32)
+}
+
+fn color(summary: &LanguageSummary) -> String {
+	summary
+		.info()
+		.color
+		.map(|color| color.hex())
+		.unwrap_or_else(|| GRAY.to_string())
+}
+
+fn text_width(text: &str) -> usize {
+	(text.chars().count() * 13).div_ceil(2)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::Language::*;
+
+	fn render(summaries: &[LanguageSummary], svg_style: SvgStyle) -> String {
+		let options = Options {
+			svg_style,
+			..Default::default()
+		};
+		let mut output = Vec::new();
+		SvgReporter::report(summaries, &options, &mut output).unwrap();
+		String::from_utf8(output).unwrap()
+	}
+
+	fn summaries() -> Vec<LanguageSummary> {
+		vec![
+			LanguageSummary {
+				language: Some(Rust),
+				lines: 2000,
+				blank_lines: 0,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![],
+			},
+			LanguageSummary {
+				language: Some(Yall),
+				lines: 1000,
+				blank_lines: 0,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![],
+			},
+		]
+	}
+
+	#[test]
+	fn bar_segments_fill_the_width() {
+		let output = render(&summaries(), SvgStyle::Bar);
+		assert!(output.contains(r##"<rect x="0" width="400" height="8" fill="#a72145">"##));
+		assert!(output.contains(r##"<rect x="400" width="200" height="8" fill="#ff8f77">"##));
+		assert!(output.contains("<title>Y&#39;all</title>"));
+		assert!(!output.contains("<text"));
+	}
+
+	#[test]
+	fn legend() {
+		let output = render(&summaries(), SvgStyle::Legend);
+		assert!(output.contains(">Rust <tspan fill=\"#57606a\">66.7%</tspan></text>"));
+		assert!(output.contains(">Y&#39;all <tspan fill=\"#57606a\">33.3%</tspan></text>"));
+	}
+
+	#[test]
+	fn badge() {
+		let output = render(&summaries(), SvgStyle::Badge);
+		assert!(output.contains("<title>lines of code: 3k</title>"));
+		assert!(output.contains("fill=\"#a72145\""));
+	}
+
+	#[test]
+	fn ids_differ_between_images() {
+		let id = |output: &str| {
+			let start = output.find("<clipPath id=\"").unwrap() + 14;
+			output[start..].split('"').next().unwrap().to_string()
+		};
+		let badge = render(&summaries(), SvgStyle::Badge);
+		let bar = render(&summaries(), SvgStyle::Bar);
+		let other_badge = render(&summaries()[..1], SvgStyle::Badge);
+		assert_ne!(id(&badge), id(&bar));
+		assert_ne!(id(&badge), id(&other_badge));
+		assert_eq!(id(&badge), id(&render(&summaries(), SvgStyle::Badge)));
+		assert!(badge.contains(&format!("clip-path=\"url(#{})\"", id(&badge))));
+	}
+}
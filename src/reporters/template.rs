@@ -0,0 +1,473 @@
+use anyhow::anyhow;
+use std::borrow::Cow;
+use std::fmt;
+use std::fmt::Display;
+use std::fs::read_to_string;
+use std::io::Write;
+
+use crate::langs::LanguageSummary;
+use crate::options::Options;
+use crate::reporters::percent;
+
+/// Renders a user provided template, so that new formats don't need their own reporter.
+///
+/// Templates are plain text with a few `{{ }}` tags mixed in:
+///
+/// - `{{ total.lines }}` prints a value, with `.` to reach into nested values
+/// - `{{#each languages}} ... {{/each}}` repeats its body for every item in a list. Inside
+///   the loop, the fields of the current item can be used directly, `this` refers to the
+///   item itself, and `@index`, `@first` and `@last` describe its position.
+/// - `{{#if blame}} ... {{else}} ... {{/if}}` and `{{#unless ...}}` render their body based
+///   on whether a value is "truthy" (not `false`, `0`, an empty string, or an empty list)
+/// - `{{! comments }}` are dropped from the output
+///
+/// Block tags which sit on a line by themselves don't leave a blank line behind.
+pub struct TemplateReporter;
+
+impl TemplateReporter {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
+		let path = options
+			.template
+			.as_ref()
+			.ok_or_else(|| anyhow!("the template reporter requires a --template file"))?;
+		let source = read_to_string(path)
+			.map_err(|err| anyhow!("failed to read template {}: {}", path.display(), err))?;
+		let template = Template::parse(&source)
+			.map_err(|err| anyhow!("failed to parse template {}: {}", path.display(), err))?;
+
+		let context = context(summaries, options);
+		let mut output = String::new();
+		template.render(&context, &mut output)?;
+		f.write_all(output.as_bytes())?;
+
+		Ok(())
+	}
+}
+
+fn context(summaries: &[LanguageSummary], options: &Options) -> Value {
+	let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
+	let total_blank_lines = summaries.iter().map(|it| it.blank_lines).sum::<usize>();
+	let total_comment_lines = summaries.iter().map(|it| it.comment_lines).sum::<usize>();
+	let total_test_lines = summaries.iter().map(|it| it.test_lines).sum::<usize>();
+	let total_files = summaries.iter().map(|it| it.files.len()).sum::<usize>();
+
+	let languages = summaries
+		.iter()
+		.map(|summary| {
+			let info = summary.info();
+			let percent = percent(summary.lines, total_lines);
+
+			Value::Map(vec![
+				("name", Value::Text(info.name)),
+				(
+					"color",
+					Value::Text(info.color.map(|color| color.hex()).unwrap_or_default()),
+				),
+				("lines", Value::Number(summary.lines)),
+				("blank_lines", Value::Number(summary.blank_lines)),
+				("comment_lines", Value::Number(summary.comment_lines)),
+				("test_lines", Value::Number(summary.test_lines)),
+				("code_lines", Value::Number(summary.code_lines())),
+				("file_count", Value::Number(summary.files.len())),
+				("percent", Value::Text(format!("{percent:.1}"))),
+				(
+					"files",
+					Value::List(
+						summary
+							.files
+							.iter()
+							.map(|file| Value::Text(file.path.display().to_string()))
+							.collect(),
+					),
+				),
+			])
+		})
+		.collect();
+
+	Value::Map(vec![
+		("root", Value::Text(options.root_label())),
+		("blame", Value::Bool(options.blame)),
+		("detailed", Value::Bool(options.detailed)),
+		(
+			"total",
+			Value::Map(vec![
+				("lines", Value::Number(total_lines)),
+				("blank_lines", Value::Number(total_blank_lines)),
+				("comment_lines", Value::Number(total_comment_lines)),
+				("test_lines", Value::Number(total_test_lines)),
+				("files", Value::Number(total_files)),
+			]),
+		),
+		("languages", Value::List(languages)),
+	])
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+	Bool(bool),
+	Number(usize),
+	Text(String),
+	List(Vec<Value>),
+	Map(Vec<(&'static str, Value)>),
+}
+
+impl Value {
+	fn get(&self, key: &str) -> Option<&Value> {
+		match self {
+			Value::Map(entries) => entries.iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+			_ => None,
+		}
+	}
+
+	fn is_truthy(&self) -> bool {
+		match self {
+			Value::Bool(b) => *b,
+			Value::Number(n) => *n != 0,
+			Value::Text(s) => !s.is_empty(),
+			Value::List(items) => !items.is_empty(),
+			Value::Map(_) => true,
+		}
+	}
+}
+
+impl Display for Value {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Value::Bool(b) => write!(f, "{b}"),
+			Value::Number(n) => write!(f, "{n}"),
+			Value::Text(s) => write!(f, "{s}"),
+			Value::List(items) => {
+				let mut items = items.iter().peekable();
+				while let Some(item) = items.next() {
+					write!(f, "{item}")?;
+					if items.peek().is_some() {
+						write!(f, ", ")?;
+					}
+				}
+				Ok(())
+			}
+			Value::Map(_) => Ok(()),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Node {
+	Text(String),
+	Variable(String),
+	Each(String, Vec<Node>),
+	If {
+		path: String,
+		negate: bool,
+		then: Vec<Node>,
+		otherwise: Vec<Node>,
+	},
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Token<'a> {
+	Text(&'a str),
+	Tag(&'a str, usize),
+}
+
+struct Template(Vec<Node>);
+
+impl Template {
+	fn parse(source: &str) -> anyhow::Result<Self> {
+		let tokens = tokenize(source)?;
+		let mut tokens = tokens.into_iter();
+		let (nodes, end) = parse_block(&mut tokens)?;
+		if let Some((tag, line)) = end {
+			return Err(anyhow!("unexpected {{{{{tag}}}}} on line {line}"));
+		}
+		Ok(Self(nodes))
+	}
+
+	fn render(&self, context: &Value, output: &mut String) -> anyhow::Result<()> {
+		let mut scopes = vec![Scope {
+			value: context,
+			position: None,
+		}];
+		render(&self.0, &mut scopes, output)
+	}
+}
+
+fn is_block_tag(tag: &str) -> bool {
+	tag.starts_with(['#', '/', '!']) || tag == "else"
+}
+
+fn tokenize(source: &str) -> anyhow::Result<Vec<Token<'_>>> {
+	let mut tokens = vec![];
+	let mut rest = source;
+
+	while let Some(start) = rest.find("{{") {
+		let offset = source.len() - rest.len();
+		let line = source[..offset + start].matches('\n').count() + 1;
+		let mut text = &rest[..start];
+		let after_open = &rest[start + 2..];
+		let end = after_open
+			.find("}}")
+			.ok_or_else(|| anyhow!("unclosed {{{{ on line {line}"))?;
+		let tag = after_open[..end].trim();
+		let mut after = &after_open[end + 2..];
+
+		// If a block tag is the only thing on its line, then remove the whole line, so that
+		// loops and conditionals don't leave a trail of blank lines in the output.
+		if is_block_tag(tag) {
+			let line_start = text.rfind('\n').map(|i| i + 1).unwrap_or(0);
+			let at_line_start = text[line_start..].trim().is_empty()
+				&& (offset + line_start == 0 || source[..offset + line_start].ends_with('\n'));
+			let line_end = after.find('\n').map(|i| i + 1).unwrap_or(after.len());
+			let at_line_end = after[..line_end].trim().is_empty();
+			if at_line_start && at_line_end {
+				text = &text[..line_start];
+				after = &after[line_end..];
+			}
+		}
+
+		if !text.is_empty() {
+			tokens.push(Token::Text(text));
+		}
+		tokens.push(Token::Tag(tag, line));
+		rest = after;
+	}
+
+	if !rest.is_empty() {
+		tokens.push(Token::Text(rest));
+	}
+
+	Ok(tokens)
+}
+
+type BlockEnd<'a> = Option<(&'a str, usize)>;
+
+fn parse_block<'a>(
+	tokens: &mut impl Iterator<Item = Token<'a>>,
+) -> anyhow::Result<(Vec<Node>, BlockEnd<'a>)> {
+	let mut nodes = vec![];
+
+	while let Some(token) = tokens.next() {
+		let (tag, line) = match token {
+			Token::Text(text) => {
+				nodes.push(Node::Text(text.to_string()));
+				continue;
+			}
+			Token::Tag(tag, line) => (tag, line),
+		};
+
+		if tag.starts_with('!') {
+			continue;
+		}
+
+		if tag == "else" || tag.starts_with('/') {
+			return Ok((nodes, Some((tag, line))));
+		}
+
+		let Some(block) = tag.strip_prefix('#') else {
+			if tag.is_empty() {
+				return Err(anyhow!("empty {{{{}}}} on line {line}"));
+			}
+			nodes.push(Node::Variable(tag.to_string()));
+			continue;
+		};
+
+		let (kind, path) = block
+			.split_once(char::is_whitespace)
+			.map(|(kind, path)| (kind, path.trim()))
+			.ok_or_else(|| anyhow!("expected a value after {{{{#{block}}}}} on line {line}"))?;
+		let close = format!("/{kind}");
+
+		let (body, end) = parse_block(tokens)?;
+		let (otherwise, end) = match end {
+			Some(("else", line)) if kind != "each" => {
+				let (otherwise, end) = parse_block(tokens)?;
+				if end.is_some_and(|(tag, _)| tag == "else") {
+					return Err(anyhow!("unexpected {{{{else}}}} on line {line}"));
+				}
+				(otherwise, end)
+			}
+			end => (vec![], end),
+		};
+
+		match end {
+			Some((tag, _)) if tag == close => {}
+			Some((tag, line)) => return Err(anyhow!("unexpected {{{{{tag}}}}} on line {line}")),
+			None => return Err(anyhow!("unclosed {{{{#{kind}}}}} on line {line}")),
+		}
+
+		nodes.push(match kind {
+			"each" => Node::Each(path.to_string(), body),
+			"if" | "unless" => Node::If {
+				path: path.to_string(),
+				negate: kind == "unless",
+				then: body,
+				otherwise,
+			},
+			_ => return Err(anyhow!("unknown block {{{{#{kind}}}}} on line {line}")),
+		});
+	}
+
+	Ok((nodes, None))
+}
+
+struct Scope<'a> {
+	value: &'a Value,
+	position: Option<(usize, usize)>,
+}
+
+fn lookup<'a>(scopes: &[Scope<'a>], path: &str) -> anyhow::Result<Cow<'a, Value>> {
+	if let Some(name) = path.strip_prefix('@') {
+		let (index, len) = scopes
+			.iter()
+			.rev()
+			.find_map(|scope| scope.position)
+			.ok_or_else(|| anyhow!("{path} can only be used inside of {{{{#each}}}}"))?;
+		let value = match name {
+			"index" => Value::Number(index),
+			"first" => Value::Bool(index == 0),
+			"last" => Value::Bool(index + 1 == len),
+			_ => return Err(anyhow!("unknown value {path}")),
+		};
+		return Ok(Cow::Owned(value));
+	}
+
+	let mut segments = path.split('.');
+	let first = segments.next().unwrap_or_default();
+	let mut value = if first == "this" {
+		scopes.last().map(|scope| scope.value)
+	} else {
+		scopes.iter().rev().find_map(|scope| scope.value.get(first))
+	}
+	.ok_or_else(|| anyhow!("unknown value {path}"))?;
+
+	for segment in segments {
+		value = value
+			.get(segment)
+			.ok_or_else(|| anyhow!("unknown value {path}"))?;
+	}
+
+	Ok(Cow::Borrowed(value))
+}
+
+fn render<'a>(
+	nodes: &[Node],
+	scopes: &mut Vec<Scope<'a>>,
+	output: &mut String,
+) -> anyhow::Result<()> {
+	for node in nodes {
+		match node {
+			Node::Text(text) => output.push_str(text),
+			Node::Variable(path) => output.push_str(&lookup(scopes, path)?.to_string()),
+			Node::If {
+				path,
+				negate,
+				then,
+				otherwise,
+			} => {
+				let branch = if lookup(scopes, path)?.is_truthy() != *negate {
+					then
+				} else {
+					otherwise
+				};
+				render(branch, scopes, output)?;
+			}
+			Node::Each(path, body) => {
+				let Cow::Borrowed(Value::List(items)) = lookup(scopes, path)? else {
+					return Err(anyhow!("{path} is not a list"));
+				};
+				for (index, item) in items.iter().enumerate() {
+					scopes.push(Scope {
+						value: item,
+						position: Some((index, items.len())),
+					});
+					let result = render(body, scopes, output);
+					scopes.pop();
+					result?;
+				}
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use crate::langs::Language::*;
+
+	fn render_with(source: &str, summaries: &[LanguageSummary]) -> anyhow::Result<String> {
+		let template = Template::parse(source)?;
+		let mut output = String::new();
+		template.render(&context(summaries, &Options::default()), &mut output)?;
+		Ok(output)
+	}
+
+	fn summaries() -> Vec<LanguageSummary> {
+		vec![
+			LanguageSummary {
+				language: Some(Rust),
+				lines: 300,
+				blank_lines: 30,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![FileSummary::named("main.rs"), FileSummary::named("lib.rs")],
+			},
+			LanguageSummary {
+				language: Some(Toml),
+				lines: 100,
+				blank_lines: 10,
+				comment_lines: 0,
+				test_lines: 0,
+				files: vec![FileSummary::named("Cargo.toml")],
+			},
+		]
+	}
+
+	#[test]
+	fn variables() -> anyhow::Result<()> {
+		assert_eq!(
+			render_with("{{ total.lines }} lines in {{root}}", &summaries())?,
+			"400 lines in ."
+		);
+		assert!(render_with("{{ nope }}", &summaries()).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn loops_and_conditionals() -> anyhow::Result<()> {
+		let source = "\
+			{{#each languages}}\n\
+			{{name}} ({{color}}): {{percent}}%{{#if @last}}.{{else}},{{/if}}\n\
+			{{/each}}\n\
+			{{#unless blame}}\n\
+			no blame\n\
+			{{/unless}}\n";
+		assert_eq!(
+			render_with(source, &summaries())?,
+			"Rust (#a72145): 75.0%,\nTOML (): 25.0%.\nno blame\n"
+		);
+
+		let source = "{{#each languages}}{{#each files}}{{this}} {{name}};{{/each}}{{/each}}";
+		assert_eq!(
+			render_with(source, &summaries())?,
+			"main.rs Rust;lib.rs Rust;Cargo.toml TOML;"
+		);
+
+		Ok(())
+	}
+
+	#[test]
+	fn parse_errors() {
+		assert!(Template::parse("{{#each languages}}").is_err());
+		assert!(Template::parse("{{/if}}").is_err());
+		assert!(Template::parse("{{#if a}}{{/each}}").is_err());
+		assert!(Template::parse("{{ oops").is_err());
+		assert!(Template::parse("{{#each languages}}{{else}}{{/each}}").is_err());
+	}
+}
@@ -1,18 +1,22 @@
 use colored::Colorize;
 use std::fmt;
 use std::fmt::Display;
+use std::io::Write;
 use terminal_size::terminal_size;
 use terminal_size::Width;
 
-use crate::langs::LanguageInfo;
+use crate::langs::CategorySummary;
 use crate::langs::LanguageSummary;
 use crate::options::Options;
 
 pub struct TerminalReporter;
 
 impl TerminalReporter {
-	pub fn report(summaries: Vec<LanguageSummary>, options: Options) -> anyhow::Result<()> {
-		let dir_path = &options.root_dir;
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
 		let term_size = terminal_size();
 		let width = match term_size {
 			Some((Width(w), _)) => w.into(),
@@ -20,52 +24,74 @@ impl TerminalReporter {
 		};
 		let inner_width = width - 2; // we have a padding of 1 character on each side
 
-		println!();
+		writeln!(f)?;
+		if options.per_root {
+			writeln!(f, " {}", options.root_label().bold())?;
+			writeln!(f)?;
+		}
 		for summary in summaries.iter() {
-			println!(
+			writeln!(
+				f,
 				" {:width$}",
-				TerminalLanguageSummary::new(summary, &options),
+				TerminalLanguageSummary::new(summary, options),
 				width = inner_width
-			)
+			)?;
+		}
+
+		if options.detailed {
+			let categories = CategorySummary::from_summaries(summaries);
+			if !categories.is_empty() {
+				writeln!(f)?;
+			}
+			for category in categories {
+				let name = category.category.to_string();
+				let right_side = details(
+					category.lines,
+					category.blank_lines,
+					category.comment_lines,
+					category.test_lines,
+				);
+				// Line up with the language names above, in place of their colored circles
+				let width = inner_width - (name.len() + 4) - (right_side.len() + 1);
+				let inlay = format!("{:.>width$}", "", width = width).bright_black();
+				writeln!(f, "    {} {} {}", name.italic(), inlay, right_side)?;
+			}
 		}
 
-		let total_lines = summaries.iter().map(|summary| summary.lines).sum();
+		let total_lines = summaries.iter().map(|summary| summary.lines).sum::<usize>();
 
 		if total_lines == 0 {
-			eprintln!(" no code found in {}", dir_path.display());
+			eprintln!(" no code found in {}", options.root_label());
 			return Ok(());
 		}
 
 		let mut filled = 0;
 
 		for summary in summaries.iter() {
-			// If there are 0 total lines, then just say everything is 0%.
-			let percent = (summary.lines * inner_width)
-				.checked_div(total_lines)
-				.unwrap_or(0);
+			let percent = summary.lines * inner_width / total_lines;
 			if percent == 0 {
 				continue;
 			}
 
 			// Print padding and such on first fill
 			if filled == 0 {
-				println!();
-				print!(" ");
+				writeln!(f)?;
+				write!(f, " ")?;
 			}
 			filled += percent;
 
-			let lang = LanguageInfo::from(&summary.language);
+			let lang = summary.info();
 			match lang.color {
-				Some(color) => print!("{}", color.on_color(&*" ".repeat(percent))),
-				None => print!("{}", " ".repeat(percent).on_white()),
+				Some(color) => write!(f, "{}", color.on_color(&*" ".repeat(percent)))?,
+				None => write!(f, "{}", " ".repeat(percent).on_white())?,
 			};
 		}
 
 		// Don't print a bar at all if it'd just all be uncategorized.
 		if filled != 0 {
-			print!("{}", " ".repeat(inner_width - filled).on_white());
-			println!();
-			println!();
+			write!(f, "{}", " ".repeat(inner_width - filled).on_white())?;
+			writeln!(f)?;
+			writeln!(f)?;
 		}
 
 		Ok(())
@@ -88,9 +114,14 @@ impl<'a, 'b> Display for TerminalLanguageSummary<'a, 'b> {
 		let TerminalLanguageSummary(summary, options) = self;
 
 		// We have to count this length by hand because, unfortunately, escape codes count
-		let left_side_width = summary.language.info().name.len() + 4; // circle + 2 leading spaces + 1 trailing space
+		let left_side_width = summary.info().name.len() + 4; // circle + 2 leading spaces + 1 trailing space
 		let right_side = if options.detailed {
-			format!("{} - {}", summary.lines, summary.blank_lines)
+			details(
+				summary.lines,
+				summary.blank_lines,
+				summary.comment_lines,
+				summary.test_lines,
+			)
 		} else {
 			format!("{}", summary.lines)
 		};
@@ -99,7 +130,7 @@ impl<'a, 'b> Display for TerminalLanguageSummary<'a, 'b> {
 			.bright_black()
 			.to_string();
 
-		let info = LanguageInfo::from(&summary.language);
+		let info = summary.info();
 		write!(
 			f,
 			"{}  {} {} {}",
@@ -116,10 +147,28 @@ impl<'a, 'b> Display for TerminalLanguageSummary<'a, 'b> {
 			let mut files = summary.files.iter().peekable();
 			while let Some(file) = files.next() {
 				let graph_char = if files.peek().is_some() { '├' } else { '└' };
-				write!(f, "\n {} {}", graph_char, file.display())?;
+				write!(f, "\n {} {}", graph_char, file.path.display())?;
 			}
 		}
 
 		Ok(())
 	}
 }
+
+/// The right side of a row with `--detailed`, shared by languages and categories so that they
+/// line up, like "120 - 14 (9 comments, 5 in tests)"
+fn details(lines: usize, blank_lines: usize, comment_lines: usize, test_lines: usize) -> String {
+	let mut extra = Vec::new();
+	match comment_lines {
+		0 => {}
+		1 => extra.push("1 comment".to_string()),
+		_ => extra.push(format!("{comment_lines} comments")),
+	}
+	if test_lines > 0 {
+		extra.push(format!("{test_lines} in tests"));
+	}
+	match extra.is_empty() {
+		true => format!("{lines} - {blank_lines}"),
+		false => format!("{lines} - {blank_lines} ({})", extra.join(", ")),
+	}
+}
@@ -1,19 +1,22 @@
 use crate::langs::LanguageSummary;
 use crate::options::Options;
-use std::fmt::Write;
+use std::io::Write;
 
 pub struct TotalLinesReporter;
 
 impl TotalLinesReporter {
-	pub fn report(summaries: Vec<LanguageSummary>, options: Options) -> anyhow::Result<()> {
+	pub fn report(
+		summaries: &[LanguageSummary],
+		options: &Options,
+		f: &mut dyn Write,
+	) -> anyhow::Result<()> {
 		let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
-		let mut output = String::new();
-		write!(&mut output, "{total_lines}")?;
+		write!(f, "{total_lines}")?;
 		if options.detailed {
 			let total_blank_lines = summaries.iter().map(|it| it.blank_lines).sum::<usize>();
-			write!(&mut output, " ({total_blank_lines} blank lines)")?;
+			write!(f, " ({total_blank_lines} blank lines)")?;
 		}
-		println!("{output}");
+		writeln!(f)?;
 
 		Ok(())
 	}
@@ -0,0 +1,76 @@
+use std::io::Write;
+
+use crate::langs::UnknownSummary;
+use crate::reporters::bold;
+
+pub struct UnknownReporter;
+
+impl UnknownReporter {
+	pub fn report(unknown: &[UnknownSummary], color: bool, f: &mut dyn Write) -> anyhow::Result<()> {
+		writeln!(f)?;
+		if unknown.is_empty() {
+			writeln!(f, " every file was recognized")?;
+			writeln!(f)?;
+			return Ok(());
+		}
+
+		writeln!(f, " {}", bold("unrecognized files", color))?;
+		let name_width = unknown.iter().map(|it| it.name.len()).max().unwrap_or(0);
+		let files_width = unknown
+			.iter()
+			.map(|it| it.file_count.to_string().len())
+			.max()
+			.unwrap_or(0);
+		let lines_width = unknown
+			.iter()
+			.map(|it| it.lines.to_string().len())
+			.max()
+			.unwrap_or(0);
+
+		for summary in unknown {
+			writeln!(
+				f,
+				"  {:name_width$}  {:>files_width$} {:5}  {:>lines_width$} lines",
+				summary.name,
+				summary.file_count,
+				if summary.file_count == 1 {
+					"file"
+				} else {
+					"files"
+				},
+				summary.lines,
+			)?;
+		}
+		writeln!(f)?;
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn columns() {
+		let unknown = [
+			UnknownSummary {
+				name: ".lock".to_string(),
+				file_count: 12,
+				lines: 5321,
+			},
+			UnknownSummary {
+				name: "LICENSE".to_string(),
+				file_count: 1,
+				lines: 21,
+			},
+		];
+		let mut output = Vec::new();
+		UnknownReporter::report(&unknown, false, &mut output).unwrap();
+
+		assert_eq!(
+			String::from_utf8(output).unwrap(),
+			"\n unrecognized files\n  .lock    12 files  5321 lines\n  LICENSE   1 file     21 lines\n\n"
+		);
+	}
+}
@@ -1,53 +1,611 @@
 use anyhow::anyhow;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::overrides::Override;
+use ignore::overrides::OverrideBuilder;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
+use std::env;
+use std::fmt;
+use std::fmt::Display;
+use std::fs;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::io;
-use std::io::Write;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::sync::Mutex;
 use std::thread::spawn;
 
 use crate::config::default_ignore_rule;
+use crate::copy_paste;
+use crate::copy_paste::CopyPaste;
+use crate::copy_paste::SourceFile;
+use crate::fc::classify;
+use crate::fc::code_lines;
+use crate::fc::is_test_in;
+use crate::fc::read_text;
+use crate::fc::CodeLine;
 use crate::fc::FileContent;
+use crate::langs::DuplicateGroup;
 use crate::langs::Language;
 use crate::langs::LanguageSummary;
+use crate::langs::UnknownSummary;
 use crate::options::Options;
-use crate::reporters::html::HtmlReporter;
-use crate::reporters::markdown::MarkdownReporter;
-use crate::reporters::terminal::TerminalReporter;
-use crate::reporters::total_lines::TotalLinesReporter;
-use crate::reporters::Reporter::*;
+use crate::reporters::percent;
 
-pub fn scan(options: Options) -> anyhow::Result<()> {
-	let mut summaries: HashMap<Language, LanguageSummary> = Default::default();
-	let dir_path = &options.root_dir;
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortBy {
+	#[default]
+	Lines,
+	Code,
+	Comments,
+	Blank,
+	Files,
+	Name,
+	Percent,
+}
+
+impl FromStr for SortBy {
+	type Err = ();
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s.to_ascii_lowercase().as_ref() {
+			"lines" => Ok(Self::Lines),
+			"code" => Ok(Self::Code),
+			"comments" => Ok(Self::Comments),
+			"blank" => Ok(Self::Blank),
+			"files" => Ok(Self::Files),
+			"name" => Ok(Self::Name),
+			"percent" => Ok(Self::Percent),
+			_ => Err(()),
+		}
+	}
+}
 
-	if !dir_path.is_dir() {
-		return Err(anyhow!("{} is not a directory", dir_path.display()));
+impl SortBy {
+	pub fn help() -> &'static str {
+		r#""lines", "code", "comments", "blank", "files", "name", "percent""#
 	}
+}
 
-	let (tx, rx) = channel();
-	let mut walk = ignore::WalkBuilder::new(dir_path);
+/// Sorts summaries from largest to smallest (or alphabetically, when sorting by name), using
+/// the language name to break ties so that the order is always stable.
+pub fn sort(summaries: &mut [LanguageSummary], sort_by: SortBy, reverse: bool) {
+	let name = |summary: &LanguageSummary| summary.info().name.to_ascii_lowercase();
+
+	summaries.sort_by(|a, b| {
+		match sort_by {
+			// Every language's percentage is relative to the same total, so sorting by
+			// percentage is the same as sorting by lines.
+			SortBy::Lines | SortBy::Percent => b.lines.cmp(&a.lines),
+			SortBy::Code => b.code_lines().cmp(&a.code_lines()),
+			SortBy::Comments => b.comment_lines.cmp(&a.comment_lines),
+			SortBy::Blank => b.blank_lines.cmp(&a.blank_lines),
+			SortBy::Files => b.files.len().cmp(&a.files.len()),
+			SortBy::Name => name(a).cmp(&name(b)),
+		}
+		.then_with(|| name(a).cmp(&name(b)))
+	});
+
+	if reverse {
+		summaries.reverse();
+	}
+}
+
+/// Combines languages which didn't make the cut for `--top` or `--min-percent` into a single
+/// "Other" summary at the end of the list, so that totals still reflect the whole codebase.
+pub fn roll_up(
+	summaries: &mut Vec<LanguageSummary>,
+	head: Option<usize>,
+	min_percent: Option<f64>,
+) {
+	let total_lines = summaries.iter().map(|it| it.lines).sum::<usize>();
+	let mut other = LanguageSummary::other();
+	let mut rolled_up = false;
+
+	let mut kept = 0;
+	for summary in std::mem::take(summaries) {
+		// Without any lines, there's nothing to take a percentage of, but `--top` still applies
+		let too_small =
+			total_lines > 0 && min_percent.is_some_and(|min| percent(summary.lines, total_lines) < min);
+		let too_many = head.is_some_and(|max| kept >= max);
+
+		if too_small || too_many {
+			other.merge(summary);
+			rolled_up = true;
+		} else {
+			summaries.push(summary);
+			kept += 1;
+		}
+	}
+
+	if rolled_up {
+		summaries.push(other);
+	}
+}
+
+/// Everything found by a scan
+#[derive(Clone, Debug)]
+pub struct Scan {
+	pub summaries: Vec<LanguageSummary>,
+	/// Only collected when `--unknown` is set, since it means reading files that would otherwise
+	/// be skipped.
+	pub unknown: Vec<UnknownSummary>,
+	/// Anything that couldn't be read, and so isn't included in the totals
+	pub diagnostics: Vec<Diagnostic>,
+	/// How many files and directories each of the default ignores skipped, most first
+	pub ignored_by_default: Vec<(String, usize)>,
+	/// How many files or directories were left out by limits like `--max-depth`, by reason
+	pub skipped: Vec<(&'static str, usize)>,
+	/// Files whose contents are exactly the same, with the most wasted lines first. Only collected
+	/// when `--duplicates` or `--dedupe` is set, since it means reading some files twice.
+	pub duplicates: Vec<DuplicateGroup>,
+	/// Only collected when `--copy-paste` is set
+	pub copy_paste: CopyPaste,
+}
+
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+	pub path: Option<PathBuf>,
+	pub message: String,
+}
+
+impl Diagnostic {
+	fn new(path: impl Into<PathBuf>, message: impl Display) -> Self {
+		Self {
+			path: Some(path.into()),
+			message: message.to_string(),
+		}
+	}
+}
+
+impl From<ignore::Error> for Diagnostic {
+	fn from(err: ignore::Error) -> Self {
+		match err {
+			ignore::Error::WithPath { path, err } => Diagnostic::new(path, err),
+			ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+				Diagnostic::from(*err)
+			}
+			err => Self {
+				path: None,
+				message: err.to_string(),
+			},
+		}
+	}
+}
+
+impl Display for Diagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match &self.path {
+			Some(path) => write!(f, "{}: {}", path.display(), self.message),
+			None => write!(f, "{}", self.message),
+		}
+	}
+}
+
+/// What became of each file. Known files come with the length and a hash of their text, if
+/// they're needed for finding duplicates, and their lines of code, if they're needed for
+/// `--copy-paste`.
+enum Scanned {
+	Known(FileContent, Option<(usize, u64)>, Vec<CodeLine>),
+	Unknown { lines: usize },
+	Failed(io::Error),
+}
+
+/// Whether files of a language should be counted, according to --exclude, --only and
+/// --category
+fn is_counted(language: Language, options: &Options) -> bool {
+	!options.excluded.contains(&language)
+		&& (options.only_include.is_empty() || options.only_include.contains(&language))
+		&& (options.categories.is_empty()
+			|| language
+				.info()
+				.category
+				.is_some_and(|category| options.categories.contains(&category)))
+}
+
+fn fingerprint(text: &str) -> (usize, u64) {
+	let mut hasher = DefaultHasher::new();
+	text.hash(&mut hasher);
+	(text.len(), hasher.finish())
+}
+
+/// Files with the same fingerprint are only probably the same, and --dedupe leaves them out
+/// of the totals, so they have to be compared properly before calling them duplicates
+fn same_contents(a: &Path, b: &Path) -> bool {
+	match (fs::read(a), fs::read(b)) {
+		(Ok(a), Ok(b)) => a == b,
+		_ => false,
+	}
+}
+
+/// Groups unrecognized files by extension, since that's usually what's missing from the
+/// language table, falling back to the whole file name for files without one.
+fn unknown_name(path: &Path) -> String {
+	match path.extension() {
+		Some(ext) => format!(".{}", ext.to_string_lossy().to_ascii_lowercase()),
+		None => path
+			.file_name()
+			.map(|name| name.to_string_lossy().to_string())
+			.unwrap_or_default(),
+	}
+}
+
+pub const KC_IGNORE: &str = ".kcignore";
+
+/// How many files or directories were skipped for each reason, other than ignore files, so
+/// that limits like `--max-depth` don't go unnoticed
+type Skipped = Arc<Mutex<BTreeMap<&'static str, usize>>>;
+
+const DEEPER_THAN_MAX_DEPTH: &str = "files or directories deeper than --max-depth";
+const ON_OTHER_FILE_SYSTEMS: &str = "directories on other file systems";
+const LARGER_THAN_MAX_FILESIZE: &str = "files larger than --max-filesize";
+const SYMLINK_LOOPS: &str = "symbolic links which loop back to a parent directory";
+
+fn skip(skipped: &Skipped, reason: &'static str) {
+	*skipped.lock().unwrap().entry(reason).or_default() += 1;
+}
+
+/// Which of the rules for skipping files the walker should follow. Scans always follow all of
+/// them, but `kc explain` turns them off one at a time, to find out which of them skipped a path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rules {
+	pub path_globs: bool,
+	pub ignore_files: bool,
+	pub hidden: bool,
+	pub max_depth: bool,
+	pub one_file_system: bool,
+	pub default_ignores: bool,
+	pub max_filesize: bool,
+}
+
+impl Rules {
+	pub const ALL: Rules = Rules {
+		path_globs: true,
+		ignore_files: true,
+		hidden: true,
+		max_depth: true,
+		one_file_system: true,
+		default_ignores: true,
+		max_filesize: true,
+	};
+}
+
+/// Sets up a walk over every root. With `towards`, the walk only goes into the directories
+/// leading to that path, which is much quicker when that's the only one that matters.
+fn walk(
+	options: &Options,
+	rules: Rules,
+	towards: Option<PathBuf>,
+	ignored_by_default: Arc<Mutex<BTreeMap<String, usize>>>,
+	skipped: Skipped,
+) -> anyhow::Result<ignore::Walk> {
+	let Some((first_root, other_roots)) = options.roots.split_first() else {
+		return Err(anyhow!("nothing to scan"));
+	};
+	for root in &options.roots {
+		if !root.exists() {
+			return Err(anyhow!("{} is not a file or directory", root.display()));
+		}
+	}
+
+	let respect_ignores = rules.ignore_files && !options.include_ignored;
+	let mut walk = ignore::WalkBuilder::new(first_root);
+	for root in other_roots {
+		walk.add(root);
+	}
 	walk
-		.hidden(!options.include_hidden)
-		.ignore(!options.include_ignored)
-		.git_ignore(!options.include_ignored)
-		.git_exclude(!options.include_ignored);
+		.hidden(rules.hidden && !options.include_hidden)
+		.ignore(respect_ignores)
+		.git_ignore(respect_ignores)
+		.git_exclude(respect_ignores)
+		.git_global(respect_ignores)
+		// The walker keeps track of the directories it's inside of, to stop at loops
+		.follow_links(options.follow_symlinks);
+	if respect_ignores {
+		// Like .ignore, but only for kc, so that fixtures can be left out of line counts
+		// without hiding them from git or ripgrep
+		walk.add_custom_ignore_filename(KC_IGNORE);
+	}
+	let explicit_ignores = match respect_ignores && !options.ignore_files.is_empty() {
+		true => Some(explicit_ignores(options)?),
+		false => None,
+	};
+	let filter = match rules.path_globs {
+		true => path_filter(options)?,
+		false => Override::empty(),
+	};
+	// Mount points are found by hand where we can, so that they can be counted
+	#[cfg(not(unix))]
+	walk.same_file_system(rules.one_file_system && options.one_file_system);
 
 	// Also ignore some known obnoxious files by default
-	if !options.include_ignored {
-		walk.filter_entry(default_ignore_rule);
+	let default_rule = (rules.default_ignores && !options.include_ignored)
+		.then(|| default_ignore_rule(options, ignored_by_default));
+	let max_depth = options.max_depth.filter(|_| rules.max_depth);
+	let one_file_system = rules.one_file_system && options.one_file_system;
+	let max_filesize = options.max_filesize.filter(|_| rules.max_filesize);
+	walk.overrides(filter.clone());
+	walk.filter_entry(move |entry| {
+		if towards
+			.as_ref()
+			.is_some_and(|towards| !towards.starts_with(entry.path()))
+		{
+			return false;
+		}
+		if max_depth.is_some_and(|max_depth| entry.depth() > max_depth) {
+			skip(&skipped, DEEPER_THAN_MAX_DEPTH);
+			return false;
+		}
+		if one_file_system && is_mount_point(entry) {
+			skip(&skipped, ON_OTHER_FILE_SYSTEMS);
+			return false;
+		}
+		if let Some(explicit_ignores) = &explicit_ignores {
+			// Paths matched by --include are kept, like they are with every other ignore file
+			let is_dir = entry.file_type().is_some_and(|it| it.is_dir());
+			if explicit_ignores.matched(entry.path(), is_dir).is_ignore()
+				&& !filter.matched(entry.path(), is_dir).is_whitelist()
+			{
+				return false;
+			}
+		}
+		if !default_rule.as_ref().is_none_or(|rule| rule(entry)) {
+			return false;
+		}
+		let is_file = entry.file_type().is_some_and(|it| it.is_file());
+		if is_file && is_too_large(entry.path(), max_filesize) {
+			skip(&skipped, LARGER_THAN_MAX_FILESIZE);
+			return false;
+		}
+		true
+	});
+
+	Ok(walk.build())
+}
+
+/// Finds which of the paths leading to `path` (and including it), which has to be inside of
+/// `root`, a scan of `root` would reach with the given rules, in the same way that it would be
+/// scanned. This is what lets `kc explain` agree with the walker, rather than guessing.
+pub fn reached(
+	options: &Options,
+	rules: Rules,
+	root: &Path,
+	path: &Path,
+) -> anyhow::Result<HashSet<PathBuf>> {
+	let options = Options {
+		roots: vec![root.to_path_buf()],
+		..options.clone()
+	};
+	let walk = walk(
+		&options,
+		rules,
+		Some(path.to_path_buf()),
+		Default::default(),
+		Default::default(),
+	)?;
+	Ok(
+		walk
+			.filter_map(|entry| entry.ok())
+			.map(|entry| entry.into_path())
+			.collect(),
+	)
+}
+
+/// Reads the files given to `--ignore-file`. Their patterns are anchored to the current
+/// directory, like `--include` and `--exclude-path`, rather than to wherever the ignore file
+/// is, so that they match the same paths no matter how the paths to scan are written.
+pub fn explicit_ignores(options: &Options) -> anyhow::Result<Gitignore> {
+	let mut builder = GitignoreBuilder::new(env::current_dir()?);
+	for path in &options.ignore_files {
+		if let Some(err) = builder.add(path) {
+			return Err(anyhow!("unable to read {}: {}", path.display(), err));
+		}
 	}
+	Ok(builder.build()?)
+}
+
+fn is_too_large(path: &Path, max_filesize: Option<u64>) -> bool {
+	max_filesize
+		.is_some_and(|max_filesize| fs::metadata(path).is_ok_and(|it| it.len() > max_filesize))
+}
+
+#[cfg(unix)]
+fn is_mount_point(entry: &ignore::DirEntry) -> bool {
+	use std::os::unix::fs::MetadataExt;
+
+	let (Ok(metadata), Some(parent)) = (entry.metadata(), entry.path().parent()) else {
+		return false;
+	};
+	metadata.is_dir() && fs::metadata(parent).is_ok_and(|parent| parent.dev() != metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn is_mount_point(_: &ignore::DirEntry) -> bool {
+	false
+}
+
+fn is_loop(err: &ignore::Error) -> bool {
+	match err {
+		ignore::Error::Loop { .. } => true,
+		ignore::Error::WithPath { err, .. }
+		| ignore::Error::WithDepth { err, .. }
+		| ignore::Error::WithLineNumber { err, .. } => is_loop(err),
+		_ => false,
+	}
+}
+
+/// Builds the globs given to `--include` and `--exclude-path`, which are matched relative to
+/// the current directory, with the last matching glob taking precedence like in a .gitignore
+pub fn path_filter(options: &Options) -> anyhow::Result<Override> {
+	let mut filter = OverrideBuilder::new(env::current_dir()?);
+	for glob in &options.path_globs {
+		filter
+			.add(glob)
+			.map_err(|err| anyhow!("invalid path glob {:?}: {}", glob, err))?;
+	}
+	Ok(filter.build()?)
+}
+
+/// Reads the list of files given to `--files-from`, where `-` means stdin
+fn read_file_list(source: &Path) -> anyhow::Result<Vec<PathBuf>> {
+	let bytes = if source == Path::new("-") {
+		let mut bytes = Vec::new();
+		io::stdin().read_to_end(&mut bytes)?;
+		bytes
+	} else {
+		fs::read(source).map_err(|err| anyhow!("unable to read {}: {}", source.display(), err))?
+	};
+	Ok(parse_file_list(&bytes))
+}
+
+/// Splits a list of paths on NUL bytes, like from `git ls-files -z` or `fd -0`, which is the
+/// only way to list paths that contain newlines, or on newlines otherwise.
+fn parse_file_list(bytes: &[u8]) -> Vec<PathBuf> {
+	let separator = if bytes.contains(&0) { b'\0' } else { b'\n' };
+	bytes
+		.split(|byte| *byte == separator)
+		.map(|path| match separator {
+			b'\n' => path.strip_suffix(b"\r").unwrap_or(path),
+			_ => path,
+		})
+		.filter(|path| !path.is_empty())
+		.map(path_from_bytes)
+		.collect()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+	use std::os::unix::ffi::OsStrExt;
+	std::ffi::OsStr::from_bytes(bytes).into()
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+	String::from_utf8_lossy(bytes).into_owned().into()
+}
+
+pub fn scan(options: &Options) -> anyhow::Result<Scan> {
+	let mut summaries: HashMap<Language, LanguageSummary> = Default::default();
+	let (tx, rx) = channel();
+	let ignored_by_default = Arc::new(Mutex::new(BTreeMap::new()));
+	let skipped = Skipped::default();
+	let paths: Box<dyn Iterator<Item = Result<PathBuf, Diagnostic>>> = match &options.files_from {
+		Some(source) => {
+			let filter = path_filter(options)?;
+			let paths = read_file_list(source)?
+				.into_iter()
+				.filter(move |path| !filter.matched(path, false).is_ignore());
+			Box::new(paths.map(Ok))
+		}
+		None => {
+			let skipped = skipped.clone();
+			let walk = walk(
+				options,
+				Rules::ALL,
+				None,
+				ignored_by_default.clone(),
+				skipped.clone(),
+			)?;
+			Box::new(walk.filter_map(move |entry| match entry {
+				Ok(entry) => Some(Ok(entry.into_path())),
+				Err(err) if is_loop(&err) => {
+					skip(&skipped, SYMLINK_LOOPS);
+					None
+				}
+				Err(err) => Some(Err(Diagnostic::from(err))),
+			}))
+		}
+	};
+
+	let mut diagnostics = Vec::new();
+	// Roots can overlap, like `kc src src/main.rs`, but each file should only be counted once
+	let mut seen = HashSet::new();
+	for path in paths {
+		let path = match path {
+			Ok(path) => path,
+			Err(diagnostic) => {
+				diagnostics.push(diagnostic);
+				continue;
+			}
+		};
+
+		// Paths from --files-from haven't been checked by the walker
+		if fs::symlink_metadata(&path).is_err() {
+			diagnostics.push(Diagnostic::new(path, "no such file"));
+			continue;
+		}
+
+		// Without -L, symbolic links aren't followed into directories, but a link to a file is
+		// counted like any other file, so a link that doesn't lead anywhere is worth mentioning.
+		if !path.is_file() {
+			let is_link = fs::symlink_metadata(&path).is_ok_and(|it| it.file_type().is_symlink());
+			if is_link && fs::metadata(&path).is_err() {
+				let message = match fs::read_link(&path) {
+					Ok(target) => format!("broken symbolic link to {}", target.display()),
+					Err(_) => "broken symbolic link".to_string(),
+				};
+				diagnostics.push(Diagnostic::new(path, message));
+			}
+			continue;
+		}
+
+		if !seen.insert(path.canonicalize().unwrap_or_else(|_| path.clone())) {
+			continue;
+		}
+
+		// The walker skips large files itself, but it never looks at the paths it starts from,
+		// or at the ones from --files-from
+		let walked = options.files_from.is_none() && !options.roots.contains(&path);
+		if !walked && is_too_large(&path, options.max_filesize) {
+			skip(&skipped, LARGER_THAN_MAX_FILESIZE);
+			continue;
+		}
+
+		let is_test = is_test_in(&path, &options.roots);
+		let include_tests = !options.no_tests;
+		if is_test && !include_tests {
+			continue;
+		}
 
-	for path in walk
-		.build()
-		.flatten()
-		.map(|entry| entry.into_path())
-		.filter(|path| path.is_file())
-	{
 		let tx = tx.clone();
+		let include_unknown = options.unknown;
+		let copy_paste = options.copy_paste;
+		let find_duplicates = options.duplicates || options.dedupe;
 		spawn(move || {
-			if let Ok(content) = FileContent::new(path.to_path_buf()) {
-				tx.send((path, content)).unwrap();
+			let scanned = match classify(&path) {
+				Ok(Some((language, _))) => match read_text(&path) {
+					Ok(text) => Scanned::Known(
+						FileContent::from_file(language, &text, is_test, include_tests),
+						find_duplicates.then(|| fingerprint(&text)),
+						match copy_paste {
+							true => code_lines(language, &text),
+							false => vec![],
+						},
+					),
+					Err(err) => Scanned::Failed(err),
+				},
+				// Files which aren't text, which are the ones with NUL bytes in them, don't have
+				// any lines to speak of
+				Ok(None) if include_unknown => Scanned::Unknown {
+					lines: fs::read(&path)
+						.ok()
+						.filter(|bytes| !bytes.contains(&0))
+						.map(|bytes| String::from_utf8_lossy(&bytes).lines().count())
+						.unwrap_or(0),
+				},
+				Ok(None) => return,
+				Err(err) => Scanned::Failed(err),
 			};
+			tx.send((path, scanned)).unwrap();
 		});
 	}
 
@@ -56,40 +614,225 @@ pub fn scan(options: Options) -> anyhow::Result<()> {
 	// drain the channel.
 	drop(tx);
 
-	while let Ok((path, content)) = rx.recv() {
-		let summary = summaries
+	let mut unknown = HashMap::<String, UnknownSummary>::new();
+	let mut files = Vec::new();
+	while let Ok((path, scanned)) = rx.recv() {
+		match scanned {
+			Scanned::Known(content, fingerprint, code) => files.push((path, content, fingerprint, code)),
+			Scanned::Unknown { lines } => {
+				let name = unknown_name(&path);
+				let summary = unknown.entry(name.clone()).or_insert(UnknownSummary {
+					name,
+					file_count: 0,
+					lines: 0,
+				});
+				summary.file_count += 1;
+				summary.lines += lines;
+			}
+			Scanned::Failed(err) => diagnostics.push(Diagnostic::new(path, err)),
+		}
+	}
+
+	// Files arrive in whatever order their threads finish, but which copy of a duplicate gets
+	// counted with --dedupe shouldn't change from one run to the next
+	files.sort_by(|a, b| a.0.cmp(&b.0));
+	let mut copies = HashMap::<(Language, usize, u64), Vec<DuplicateGroup>>::new();
+	let mut sources = Vec::new();
+	for (path, content, fingerprint, code) in files {
+		if !is_counted(content.language, options) {
+			continue;
+		}
+
+		// Empty files are all the same as each other, but they aren't really duplicates
+		if let Some((len, hash)) = fingerprint.filter(|_| content.lines > 0) {
+			let groups = copies.entry((content.language, len, hash)).or_default();
+			match groups
+				.iter_mut()
+				.find(|group| same_contents(&group.paths[0], &path))
+			{
+				Some(group) => {
+					group.paths.push(path.clone());
+					if options.dedupe {
+						continue;
+					}
+				}
+				None => groups.push(DuplicateGroup {
+					paths: vec![path.clone()],
+					lines: content.lines,
+				}),
+			}
+		}
+
+		if options.copy_paste {
+			sources.push(SourceFile {
+				path: path.clone(),
+				language: content.language,
+				lines: code,
+			});
+		}
+
+		summaries
 			.entry(content.language)
-			.or_insert_with(|| LanguageSummary::from(content.language));
-		summary.lines += content.lines;
-		summary.blank_lines += content.blank_lines;
-		summary.files.push(path);
+			.or_insert_with(|| LanguageSummary::from(content.language))
+			.add(path, &content);
 	}
 
 	let mut summaries = summaries.into_values().collect::<Vec<_>>();
-	summaries.sort_by(|a, b| b.lines.cmp(&a.lines));
+	sort(&mut summaries, options.sort_by, options.reverse);
+	roll_up(&mut summaries, options.head, options.min_percent);
 
-	if !options.excluded.is_empty() {
-		summaries.retain(|it| !options.excluded.contains(&it.language))
-	}
+	let mut unknown = unknown.into_values().collect::<Vec<_>>();
+	unknown.sort_by(|a, b| {
+		b.lines
+			.cmp(&a.lines)
+			.then(b.file_count.cmp(&a.file_count))
+			.then_with(|| a.name.cmp(&b.name))
+	});
 
-	if !options.only_include.is_empty() {
-		summaries.retain(|it| options.only_include.contains(&it.language))
+	diagnostics.sort_by(|a, b| a.path.cmp(&b.path));
+
+	let mut duplicates = copies
+		.into_values()
+		.flatten()
+		.filter(|group| group.paths.len() > 1)
+		.collect::<Vec<_>>();
+	duplicates.sort_by(|a, b| {
+		b.wasted_lines()
+			.cmp(&a.wasted_lines())
+			.then_with(|| a.paths.cmp(&b.paths))
+	});
+
+	let ignored_by_default = ignored_by_default.lock().unwrap().clone();
+	let mut ignored_by_default = ignored_by_default.into_iter().collect::<Vec<_>>();
+	ignored_by_default.sort_by_key(|(_, count)| Reverse(*count));
+
+	let skipped = skipped.lock().unwrap().clone().into_iter().collect();
+
+	let copy_paste = copy_paste::find(&sources, options.min_block_lines);
+
+	Ok(Scan {
+		summaries,
+		unknown,
+		diagnostics,
+		ignored_by_default,
+		skipped,
+		duplicates,
+		copy_paste,
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::langs::FileSummary;
+	use Language::*;
+
+	#[test]
+	fn sorting() {
+		let summary = |language, lines, comment_lines, files| LanguageSummary {
+			lines,
+			comment_lines,
+			files: vec![FileSummary::default(); files],
+			..LanguageSummary::from(language)
+		};
+		let mut summaries = vec![
+			summary(Toml, 10, 0, 3),
+			summary(Rust, 100, 50, 1),
+			summary(Go, 80, 0, 2),
+			summary(C, 10, 0, 1),
+		];
+		let order = |summaries: &[LanguageSummary]| {
+			summaries
+				.iter()
+				.filter_map(|it| it.language)
+				.collect::<Vec<_>>()
+		};
+
+		sort(&mut summaries, SortBy::Lines, false);
+		assert_eq!(order(&summaries), [Rust, Go, C, Toml]);
+		sort(&mut summaries, SortBy::Code, false);
+		assert_eq!(order(&summaries), [Go, Rust, C, Toml]);
+		sort(&mut summaries, SortBy::Files, false);
+		assert_eq!(order(&summaries), [Toml, Go, C, Rust]);
+		sort(&mut summaries, SortBy::Name, false);
+		assert_eq!(order(&summaries), [C, Go, Rust, Toml]);
+		sort(&mut summaries, SortBy::Name, true);
+		assert_eq!(order(&summaries), [Toml, Rust, Go, C]);
 	}
 
-	if let Some(max) = &options.head {
-		summaries.truncate(*max);
+	#[test]
+	fn file_lists() {
+		let paths = |list: &[u8]| {
+			parse_file_list(list)
+				.into_iter()
+				.map(|path| path.display().to_string())
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			paths(b"src/main.rs\nREADME.md\n"),
+			["src/main.rs", "README.md"]
+		);
+		assert_eq!(paths(b"a.rs\r\n\nb.rs"), ["a.rs", "b.rs"]);
+		assert_eq!(
+			paths(b"with\nnewline.rs\0b.rs\0"),
+			["with\nnewline.rs", "b.rs"]
+		);
+		assert!(paths(b"").is_empty());
 	}
 
-	match options.reporter {
-		Html => HtmlReporter::report(summaries, options)?,
-		Markdown => write!(
-			io::stdout(),
-			"{}",
-			MarkdownReporter::new(summaries, options)
-		)?,
-		Terminal => TerminalReporter::report(summaries, options)?,
-		TotalLines => TotalLinesReporter::report(summaries, options)?,
-	};
+	#[test]
+	fn rolling_up() {
+		let summary = |language, lines| LanguageSummary {
+			lines,
+			files: vec![FileSummary::default()],
+			..LanguageSummary::from(language)
+		};
+		let summaries = vec![
+			summary(Rust, 700),
+			summary(Go, 200),
+			summary(Toml, 60),
+			summary(C, 40),
+		];
+		let order = |summaries: &[LanguageSummary]| {
+			summaries
+				.iter()
+				.map(|it| (it.language, it.lines, it.files.len()))
+				.collect::<Vec<_>>()
+		};
+
+		let mut rolled_up = summaries.clone();
+		roll_up(&mut rolled_up, Some(2), None);
+		assert_eq!(
+			order(&rolled_up),
+			[(Some(Rust), 700, 1), (Some(Go), 200, 1), (None, 100, 2)]
+		);
+
+		let mut rolled_up = summaries.clone();
+		roll_up(&mut rolled_up, None, Some(5.0));
+		assert_eq!(
+			order(&rolled_up),
+			[
+				(Some(Rust), 700, 1),
+				(Some(Go), 200, 1),
+				(Some(Toml), 60, 1),
+				(None, 40, 1)
+			]
+		);
 
-	Ok(())
+		let mut rolled_up = summaries.clone();
+		roll_up(&mut rolled_up, Some(1), Some(5.0));
+		assert_eq!(order(&rolled_up), [(Some(Rust), 700, 1), (None, 300, 3)]);
+
+		let mut rolled_up = summaries.clone();
+		roll_up(&mut rolled_up, Some(10), Some(1.0));
+		assert_eq!(order(&rolled_up), order(&summaries));
+
+		// Nothing is a percentage of nothing, so there's nothing to roll up
+		let mut empty = vec![summary(Rust, 0), summary(Go, 0)];
+		roll_up(&mut empty, None, Some(5.0));
+		assert_eq!(order(&empty), [(Some(Rust), 0, 1), (Some(Go), 0, 1)]);
+		roll_up(&mut empty, Some(1), Some(5.0));
+		assert_eq!(order(&empty), [(Some(Rust), 0, 1), (None, 0, 1)]);
+	}
 }
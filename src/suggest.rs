@@ -0,0 +1,100 @@
+/// Picks out the candidates which are closest to something that wasn't recognized, so that
+/// typos can be answered with a "did you mean" instead of just an error. Only candidates which
+/// are reasonably close are returned, and at most a few of them.
+pub fn closest<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+	let input = input.to_ascii_lowercase();
+	// Allow roughly one mistake for every three characters, so that short inputs don't match
+	// everything.
+	let max_distance = (input.chars().count() / 3).max(1);
+
+	let mut best = Vec::new();
+	let mut best_distance = max_distance + 1;
+	for candidate in candidates {
+		let distance = distance(&input, &candidate.to_ascii_lowercase());
+		if distance < best_distance {
+			best_distance = distance;
+			best.clear();
+		}
+		if distance == best_distance && !best.contains(&candidate) {
+			best.push(candidate);
+		}
+	}
+
+	best.truncate(3);
+	best
+}
+
+/// Formats the closest candidates as a hint that can be added to the end of an error message,
+/// or returns an empty string if nothing was close enough.
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> String {
+	let suggestions = closest(input, candidates)
+		.iter()
+		.map(|it| format!("\"{it}\""))
+		.collect::<Vec<_>>();
+
+	match suggestions.as_slice() {
+		[] => String::new(),
+		[only] => format!(" (did you mean {only}?)"),
+		[rest @ .., last] => format!(" (did you mean {} or {}?)", rest.join(", "), last),
+	}
+}
+
+/// The Damerau-Levenshtein (optimal string alignment) distance between two strings, which
+/// counts swapping two adjacent characters as a single edit, since that's a very common typo.
+fn distance(a: &str, b: &str) -> usize {
+	let a = a.chars().collect::<Vec<_>>();
+	let b = b.chars().collect::<Vec<_>>();
+
+	// `rows[i][j]` is the distance between the first `i` characters of `a` and the first `j`
+	// characters of `b`.
+	let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+	for (i, row) in rows.iter_mut().enumerate() {
+		row[0] = i;
+	}
+	for (j, cell) in rows[0].iter_mut().enumerate() {
+		*cell = j;
+	}
+
+	for i in 1..=a.len() {
+		for j in 1..=b.len() {
+			let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+			rows[i][j] = (rows[i - 1][j] + 1)
+				.min(rows[i][j - 1] + 1)
+				.min(rows[i - 1][j - 1] + cost);
+			if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+				rows[i][j] = rows[i][j].min(rows[i - 2][j - 2] + 1);
+			}
+		}
+	}
+
+	rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn distances() {
+		assert_eq!(distance("", ""), 0);
+		assert_eq!(distance("rust", "rust"), 0);
+		assert_eq!(distance("typscript", "typescript"), 1);
+		assert_eq!(distance("jsno", "json"), 1);
+		assert_eq!(distance("kitten", "sitting"), 3);
+	}
+
+	#[test]
+	fn suggestions() {
+		let candidates = ["typescript", "javascript", "json", "jsonc", "rust"];
+		assert_eq!(closest("typscript", candidates), ["typescript"]);
+		assert_eq!(closest("TypeScirpt", candidates), ["typescript"]);
+		assert_eq!(closest("jsn", candidates), ["json"]);
+		assert!(closest("haskell", candidates).is_empty());
+
+		assert_eq!(
+			did_you_mean("jsonn", candidates),
+			" (did you mean \"json\" or \"jsonc\"?)"
+		);
+		assert_eq!(did_you_mean("haskell", candidates), "");
+	}
+}
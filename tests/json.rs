@@ -0,0 +1,104 @@
+/// Checks that `text` is a single JSON value, with nothing but whitespace after it, which is
+/// enough to catch anything else being written to the same output as a JSON report.
+pub fn is_valid(text: &str) -> bool {
+	let mut parser = Parser {
+		bytes: text.as_bytes(),
+		at: 0,
+	};
+	parser.value() && {
+		parser.whitespace();
+		parser.at == parser.bytes.len()
+	}
+}
+
+struct Parser<'a> {
+	bytes: &'a [u8],
+	at: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<u8> {
+		self.bytes.get(self.at).copied()
+	}
+
+	fn whitespace(&mut self) {
+		while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+			self.at += 1;
+		}
+	}
+
+	fn eat(&mut self, byte: u8) -> bool {
+		self.whitespace();
+		if self.peek() == Some(byte) {
+			self.at += 1;
+			true
+		} else {
+			false
+		}
+	}
+
+	fn value(&mut self) -> bool {
+		self.whitespace();
+		match self.peek() {
+			Some(b'{') => self.list(b'}', |parser| {
+				parser.whitespace();
+				parser.string() && parser.eat(b':') && parser.value()
+			}),
+			Some(b'[') => self.list(b']', Self::value),
+			Some(b'"') => self.string(),
+			Some(b'-' | b'0'..=b'9') => {
+				let start = self.at;
+				while matches!(
+					self.peek(),
+					Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9')
+				) {
+					self.at += 1;
+				}
+				std::str::from_utf8(&self.bytes[start..self.at])
+					.is_ok_and(|number| number.parse::<f64>().is_ok())
+			}
+			_ => ["true", "false", "null"].iter().any(|word| {
+				let found = self.bytes[self.at..].starts_with(word.as_bytes());
+				if found {
+					self.at += word.len();
+				}
+				found
+			}),
+		}
+	}
+
+	fn list(&mut self, close: u8, mut item: impl FnMut(&mut Self) -> bool) -> bool {
+		self.at += 1;
+		if self.eat(close) {
+			return true;
+		}
+		loop {
+			if !item(self) {
+				return false;
+			}
+			if self.eat(close) {
+				return true;
+			}
+			if !self.eat(b',') {
+				return false;
+			}
+		}
+	}
+
+	fn string(&mut self) -> bool {
+		if self.peek() != Some(b'"') {
+			return false;
+		}
+		self.at += 1;
+		while let Some(byte) = self.peek() {
+			self.at += 1;
+			match byte {
+				b'"' => return true,
+				b'\\' => self.at += 1,
+				0..0x20 => return false,
+				_ => {}
+			}
+		}
+		false
+	}
+}
@@ -1,6 +1,10 @@
+use std::env;
+use std::fs;
 use std::path::Path;
+use std::process;
 use std::process::Command;
 
+mod json;
 mod setup;
 
 const EXE: &str = "./build/release/kc";
@@ -117,21 +121,157 @@ fn scan_nonexistent() {
 	assert!(!result.status.success());
 	let stderr = String::from_utf8_lossy(&result.stderr);
 
-	assert!(stderr.contains("is not a directory"));
+	assert!(stderr.contains("is not a file or directory"));
 }
 
 #[test]
 fn scan_file() {
 	setup::before();
 
+	// Files can be scanned directly, even if they'd be ignored in a directory
 	let result = Command::new(EXE)
-		.arg("./tests/testdata/file")
+		.args(["-l", "./tests/testdata/mixed/main.rs"])
 		.output()
 		.unwrap();
-	assert!(!result.status.success());
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "6\n");
+
+	// ...but a file that isn't code still doesn't count
+	let result = Command::new(EXE)
+		.args(["-l", "./tests/testdata/file"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "0\n");
+}
+
+#[test]
+fn scan_multiple_roots() {
+	setup::before();
+
+	// Overlapping paths are only counted once
+	let result = Command::new(EXE)
+		.args(["-l", "tests/testdata/mixed/", "tests/testdata/rust/"])
+		.args([
+			"tests/testdata/mixed/main.rs",
+			"./tests/testdata/mixed/index.ts",
+		])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let separate = Command::new(EXE)
+		.args(["-l", "tests/testdata/rust/"])
+		.output()
+		.unwrap();
+	let separate = String::from_utf8_lossy(&separate.stdout)
+		.trim()
+		.parse::<usize>()
+		.unwrap();
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, format!("{}\n", 17 + separate));
+
+	let result = Command::new(EXE)
+		.args([
+			"-l",
+			"--per-root",
+			"tests/testdata/mixed/",
+			"tests/testdata/mixed/main.rs",
+		])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "17\n6\n");
+}
+
+#[test]
+fn scan_tests() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "tests/testdata/with_tests/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains(r#""category": "programming", "lines": 17, "blank_lines": 2, "comment_lines": 0, "test_lines": 13"#));
+	assert!(stdout.contains(r#""lines": 4, "blank_lines": 1, "comment_lines": 0, "test_lines": 3"#));
+
+	// Test files are left out entirely, and so are Rust's test modules
+	let result = Command::new(EXE)
+		.args(["-l", "--no-tests", "tests/testdata/with_tests/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "5\n");
+}
+
+#[test]
+fn scan_path_globs() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-l", "tests/testdata/mixed/", "--include", "**/*.rs"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "6\n");
+
+	let result = Command::new(EXE)
+		.args(["-l", "tests/testdata/mixed/"])
+		.args(["--exclude-path", "*.rs", "--exclude-path", "Makefile"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "7\n");
+
+	// A leading ! inverts a glob, and the last one to match wins
+	let result = Command::new(EXE)
+		.args(["-l", "tests/testdata/mixed/"])
+		.args(["--include", "**/*.{rs,ts}", "--include", "!index.ts"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "6\n");
+}
+
+#[test]
+fn scan_files_from() {
+	use std::io::Write;
+
+	setup::before();
+
+	let mut child = Command::new(EXE)
+		.args(["-l", "--files-from", "-"])
+		.stdin(process::Stdio::piped())
+		.stdout(process::Stdio::piped())
+		.stderr(process::Stdio::piped())
+		.spawn()
+		.unwrap();
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(b"tests/testdata/mixed/main.rs\0tests/testdata/mixed/missing.rs\0")
+		.unwrap();
+	let result = child.wait_with_output().unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "6\n");
 	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stderr.contains("1 file or directory could not be read"));
 
-	assert!(stderr.contains("is not a directory"));
+	let result = Command::new(EXE)
+		.args(["--files-from", "-", "src/"])
+		.output()
+		.unwrap();
+	assert!(!result.status.success());
 }
 
 #[test]
@@ -146,6 +286,34 @@ fn scan_empty() {
 	let stderr = String::from_utf8_lossy(&result.stderr);
 
 	assert!(stderr.contains("no code found in"));
+
+	// Files without any lines are 0% of nothing, and shouldn't all be rolled into Other
+	let dir = env::temp_dir().join(format!("kc-empty-files-{}", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("main.rs"), "").unwrap();
+	fs::write(dir.join("main.go"), "").unwrap();
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--min-percent", "5"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains(r#""language": "Rust""#));
+	assert!(stdout.contains(r#""language": "Go""#));
+	assert!(!stdout.contains(r#""language": "Other""#));
+
+	// ...but --top still applies
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--top", "1"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout.matches(r#""language": "#).count(), 2);
+	assert!(stdout.contains(r#""language": "Other""#));
 }
 
 #[test]
@@ -202,7 +370,8 @@ fn scan_mixed() {
 		.unwrap();
 	assert!(result.status.success());
 	let stdout = String::from_utf8_lossy(&result.stdout);
-	assert_eq!(stdout, "11\n");
+	// Languages left out by --top are rolled up into "Other", so the total stays the same
+	assert_eq!(stdout, "17\n");
 
 	let result = Command::new(EXE)
 		.args(["-l", "tests/testdata/mixed/", "-x", "rust,gleam"])
@@ -226,6 +395,159 @@ fn scan_mixed_with_html_reporter() {
 	let stdout = String::from_utf8_lossy(&result.stdout);
 
 	assert_eq!(include_str!("./testdata/mixed.html"), stdout);
+
+	let result = Command::new(EXE)
+		.args(["-reporter", "html", "--blame", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("<summary>Rust (1 file)</summary>"));
+	assert!(stdout.contains("<li><code>tests/testdata/mixed/main.rs</code>"));
+}
+
+#[test]
+fn scan_mixed_with_interactive_html_reporter() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-reporter", "html-interactive", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+
+	assert!(stdout.starts_with("<!doctype html>\n"));
+	assert!(stdout.trim_end().ends_with("</html>"));
+	// Paths are relative to the root, so that the treemap starts from it
+	assert!(stdout.contains(r#"{ "name": "Rust", "#));
+	assert!(stdout.contains(r#"["main.rs", "#));
+	assert!(!stdout.contains("tests/testdata/mixed/main.rs"));
+}
+
+#[test]
+fn scan_mixed_with_multiple_reporters() {
+	setup::before();
+
+	let out_dir = env::temp_dir().join(format!("kc-multiple-reporters-{}", process::id()));
+	fs::create_dir_all(&out_dir).unwrap();
+	let html_path = out_dir.join("report.html");
+	let json_path = out_dir.join("report.json");
+
+	let result = Command::new(EXE)
+		.args(["-O", "total-lines", "tests/testdata/mixed/"])
+		.arg("-O")
+		.arg(format!("html={}", html_path.display()))
+		.arg("-O")
+		.arg(format!("json={}", json_path.display()))
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!("17\n", stdout);
+
+	let html = fs::read_to_string(&html_path).unwrap();
+	assert_eq!(include_str!("./testdata/mixed.html"), html);
+	let json = fs::read_to_string(&json_path).unwrap();
+	assert!(json.contains(
+		r#""total": { "lines": 17, "blank_lines": 3, "comment_lines": 0, "test_lines": 0, "files": 4 }"#
+	));
+	assert!(json.contains(r#""language": "Rust""#));
+
+	fs::remove_dir_all(&out_dir).unwrap();
+}
+
+#[test]
+fn scan_mixed_with_template_reporter() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args([
+			"--template",
+			"tests/testdata/templates/slack.txt",
+			"tests/testdata/mixed/",
+		])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+
+	assert_eq!(
+		stdout,
+		"*tests/testdata/mixed/* has 17 lines of code\n\
+		• Rust: 6 (35.3%),\n\
+		• Gleam: 5 (29.4%),\n\
+		• Make: 4 (23.5%),\n\
+		• TypeScript: 2 (11.8%)\n"
+	);
+}
+
+#[test]
+fn html_reporter_escapes_hostile_paths() {
+	setup::before();
+
+	let dir = env::temp_dir().join(format!("kc-<script>alert(\"{}\")<\\script>", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+
+	let result = Command::new(EXE)
+		.args(["-reporter", "html"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+
+	assert!(!stdout.contains("<script>"));
+	assert!(stdout.contains("kc-&lt;script&gt;alert(&quot;"));
+}
+
+#[test]
+fn scan_mixed_sorted() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--sort", "name", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	let languages = stdout
+		.lines()
+		.filter_map(|line| line.split("\"language\": \"").nth(1))
+		.map(|rest| rest.split('"').next().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(languages, ["Gleam", "Make", "Rust", "TypeScript"]);
+
+	// Sorting happens before truncating, so --top keeps the first languages alphabetically
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--sort", "name", "--reverse", "-t", "2"])
+		.arg("tests/testdata/mixed/")
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	let languages = stdout
+		.lines()
+		.filter_map(|line| line.split("\"language\": \"").nth(1))
+		.map(|rest| rest.split('"').next().unwrap())
+		.collect::<Vec<_>>();
+	assert_eq!(languages, ["TypeScript", "Rust", "Other"]);
+}
+
+#[test]
+fn scan_mixed_min_percent() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--min-percent", "20", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("\"language\": \"Other\""));
+	assert!(stdout.contains("\"total\": { \"lines\": 17,"));
 }
 
 #[test]
@@ -344,3 +666,501 @@ fn ignores_gitignore_file() {
 	assert!(!stdout.contains("Go"));
 	assert!(!stdout.contains("Rust"));
 }
+
+#[test]
+fn scan_mixed_by_category() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-l", "--category", "build", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "4\n");
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "-d", "tests/testdata/mixed/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("\"language\": \"Make\", \"color\": null, \"category\": \"build\""));
+	assert!(stdout.contains("{ \"category\": \"programming\", \"lines\": 13,"));
+
+	// The category rows give the same details as the language rows
+	let dir = env::temp_dir().join(format!("kc-category-details-{}", process::id()));
+	fs::create_dir_all(dir.join("tests")).unwrap();
+	fs::write(dir.join("lib.rs"), "// A comment\nfn a() {}\n\n").unwrap();
+	fs::write(dir.join("tests/a.rs"), "fn b() {}\n").unwrap();
+	let result = Command::new(EXE).arg("-d").arg(&dir).output().unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	let rows = stdout
+		.lines()
+		.filter(|line| line.ends_with(" 4 - 1 (1 comment, 1 in tests)"))
+		.count();
+	assert_eq!(rows, 2);
+}
+
+#[test]
+fn list_languages() {
+	setup::before();
+
+	let result = Command::new(EXE).args(["languages"]).output().unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout
+		.lines()
+		.any(|line| line.contains("Rust") && line.contains(".rs")));
+
+	let result = Command::new(EXE)
+		.args(["languages", "--json"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains(
+		"{ \"name\": \"C#\", \"color\": \"#058e0c\", \"category\": \"programming\", \"aliases\": [\"csharp\", \"c#\"], \"extensions\": [\"cs\"], \"file_names\": [], \"interpreters\": [] }"
+	));
+	assert!(stdout.contains("\"file_names\": [\"Makefile\"]"));
+}
+
+#[test]
+fn scan_shebang() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "tests/testdata/shebang/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("\"language\": \"Python\""));
+}
+
+#[test]
+fn explain() {
+	setup::before();
+
+	// Explain from inside of testdata, since the repository's own .ignore skips all of it
+	let exe = env::current_dir().unwrap().join(EXE);
+	let explain = |args: &[&str]| {
+		let result = Command::new(&exe)
+			.arg("explain")
+			.args(args)
+			.current_dir("tests/testdata")
+			.output()
+			.unwrap();
+		assert!(result.status.success());
+		String::from_utf8_lossy(&result.stdout).to_string()
+	};
+
+	let stdout = explain(&["mixed/main.rs"]);
+	assert!(stdout.contains("recognized as Rust by its extension \".rs\"\n  counted\n"));
+	assert!(stdout.contains("6 lines: 5 code, 0 comments, 1 blank"));
+
+	let stdout = explain(&["shebang/greet"]);
+	assert!(stdout.contains("recognized as Python by its shebang, which runs \"python\""));
+	assert!(stdout.contains("4 lines: 1 code, 2 comments, 1 blank"));
+
+	// Files ignored by a .gitignore can't be checked in, so make one to be ignored
+	let dir = env::temp_dir().join(format!("kc-explain-gitignore-{}", process::id()));
+	fs::create_dir_all(dir.join(".git")).unwrap();
+	fs::create_dir_all(dir.join("gitignore/secrets")).unwrap();
+	fs::write(dir.join("gitignore/.gitignore"), "secrets/\n").unwrap();
+	fs::write(dir.join("gitignore/secrets/main.go"), "package main\n").unwrap();
+	let result = Command::new(&exe)
+		.args(["explain", "gitignore/secrets/main.go"])
+		.current_dir(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("\"secrets/\" in gitignore/.gitignore ignores gitignore/secrets"));
+
+	let stdout = explain(&["ignore/secrets/main.go", "-A"]);
+	assert!(stdout.contains("recognized as Go"));
+
+	let stdout = explain(&["hidden/.config"]);
+	assert!(stdout.contains("hidden/.config is hidden"));
+
+	let stdout = explain(&["mixed/main.rs", "-x", "rust"]);
+	assert!(stdout.contains("skipped, because Rust is excluded by --exclude"));
+
+	let stdout = explain(&["file"]);
+	assert!(stdout.contains("its language couldn't be determined"));
+
+	let stdout = explain(&["kcignore/fixtures/fixture.rs"]);
+	assert!(stdout.contains("\"fixtures/\" in kcignore/.kcignore ignores kcignore/fixtures"));
+
+	// The repository's .ignore skips testdata/, but not when a scan starts inside of it
+	let result = Command::new(EXE)
+		.args(["explain", "tests/testdata/kcignore/lib.rs"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("\"testdata/\" in .ignore ignores tests/testdata"));
+	let result = Command::new(EXE)
+		.args(["explain", "--root", "tests/testdata/kcignore/"])
+		.arg("tests/testdata/kcignore/lib.rs")
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("  counted\n"));
+	// Being inside of tests/ doesn't make it a test, since that's outside of the scan
+	assert!(stdout.contains("1 line: 1 code, 0 comments, 0 blank\n"));
+	let result = Command::new(EXE)
+		.args([
+			"explain",
+			"--no-tests",
+			"--root",
+			"tests/testdata/with_tests/",
+		])
+		.args(["tests/testdata/with_tests/app.ts"])
+		.args(["tests/testdata/with_tests/tests/smoke.rs"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(
+		stdout.contains("app.ts\n  recognized as TypeScript by its extension \".ts\"\n  counted\n")
+	);
+	assert!(stdout.contains("skipped, because it's a test, and tests are excluded by --no-tests"));
+}
+
+#[test]
+fn scan_unknown() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["--unknown", "tests/testdata/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(
+		stdout.contains(" unrecognized files\n  .txt  1 file   4 lines\n  file  1 file   0 lines\n")
+	);
+
+	// Anything other than the terminal reporter might be parsed, so the list goes to stderr
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--unknown", "tests/testdata/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stdout.trim_end().ends_with('}'));
+	assert!(!stdout.contains("unrecognized files"));
+	assert!(stderr.contains(" unrecognized files\n"));
+}
+
+#[test]
+fn scan_duplicates() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["-l", "--duplicates", "tests/testdata/duplicates/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "10\n");
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stderr.contains(
+		"  3 copies of 3 lines, 6 wasted\n    tests/testdata/duplicates/clamp.js\n    tests/testdata/duplicates/vendored/clamp.copy.js\n"
+	));
+
+	let result = Command::new(EXE)
+		.args(["-l", "--dedupe", "tests/testdata/duplicates/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert_eq!(stdout, "4\n");
+
+	// The list can't be allowed to end up inside of a report that's meant to be parsed
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--duplicates", "tests/testdata/duplicates/"])
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert!(json::is_valid(&String::from_utf8_lossy(&result.stdout)));
+	assert!(String::from_utf8_lossy(&result.stderr).contains("duplicate files"));
+
+	// Files with the same length and different contents aren't duplicates
+	let dir = env::temp_dir().join(format!("kc-same-length-{}", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("a.js"), "let a = 1;\n").unwrap();
+	fs::write(dir.join("b.js"), "let b = 2;\n").unwrap();
+	let result = Command::new(EXE)
+		.args(["-l", "--dedupe", "--duplicates"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "2\n");
+	assert!(String::from_utf8_lossy(&result.stderr).contains("no duplicate files"));
+}
+
+#[test]
+fn scan_copy_paste() {
+	setup::before();
+
+	let result = Command::new(EXE)
+		.args(["--copy-paste", "--min-block-lines", "3"])
+		.arg("tests/testdata/duplicates/")
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("  JavaScript  6 of 10 lines of code (60.0%)\n"));
+	assert!(stdout.contains(
+		"  3 lines  tests/testdata/duplicates/clamp.js:1-3\n           tests/testdata/duplicates/vendored/clamp.copy.js:1-3\n"
+	));
+
+	// Only one copy of each file is left to compare with --dedupe
+	let result = Command::new(EXE)
+		.args(["-l", "--dedupe", "--copy-paste", "--min-block-lines", "3"])
+		.arg("tests/testdata/duplicates/")
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stderr.contains("no copy-pasted code"));
+
+	let result = Command::new(EXE)
+		.args(["-O", "json", "--copy-paste", "--min-block-lines", "3"])
+		.arg("tests/testdata/duplicates/")
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert!(json::is_valid(&String::from_utf8_lossy(&result.stdout)));
+	assert!(String::from_utf8_lossy(&result.stderr).contains("copy-pasted code"));
+}
+
+#[test]
+fn scan_kcignore() {
+	setup::before();
+
+	let count = |args: &[&str]| {
+		let result = Command::new(EXE)
+			.args(["-l", "tests/testdata/kcignore/"])
+			.args(args)
+			.output()
+			.unwrap();
+		assert!(result.status.success());
+		String::from_utf8_lossy(&result.stdout).to_string()
+	};
+
+	// fixtures/ is left out by the .kcignore file
+	assert_eq!(count(&[]), "4\n");
+	assert_eq!(count(&["-A"]), "5\n");
+
+	let ignore_file = env::temp_dir().join(format!("kc-ignore-file-{}", process::id()));
+	fs::write(&ignore_file, "lib.rs\n").unwrap();
+	let stdout = count(&["--ignore-file", ignore_file.to_str().unwrap()]);
+	assert_eq!(stdout, "3\n");
+
+	// Patterns are relative to the current directory, however the path to scan is written
+	fs::write(&ignore_file, "tests/testdata/kcignore/lib.rs\n").unwrap();
+	let absolute = env::current_dir().unwrap().join("tests/testdata/kcignore/");
+	for root in [Path::new("tests/testdata/kcignore/"), &absolute] {
+		let result = Command::new(EXE)
+			.args(["-l", "--ignore-file", ignore_file.to_str().unwrap()])
+			.arg(root)
+			.output()
+			.unwrap();
+		assert!(result.status.success());
+		assert_eq!(String::from_utf8_lossy(&result.stdout), "3\n");
+	}
+	fs::remove_file(&ignore_file).unwrap();
+
+	let result = Command::new(EXE)
+		.args(["--ignore-file", "tests/testdata/kcignore/missing"])
+		.output()
+		.unwrap();
+	assert!(!result.status.success());
+}
+
+#[test]
+fn scan_default_ignores() {
+	setup::before();
+
+	let dir = env::temp_dir().join(format!("kc-default-ignores-{}", process::id()));
+	fs::create_dir_all(dir.join("vendor/dep")).unwrap();
+	fs::create_dir_all(dir.join("target")).unwrap();
+	fs::write(dir.join("main.go"), "package main\n").unwrap();
+	fs::write(
+		dir.join("vendor/dep/dep.go"),
+		"package dep\n\nfunc Dep() {}\n",
+	)
+	.unwrap();
+	fs::write(dir.join("target/build.rs"), "fn main() {}\n").unwrap();
+
+	let result = Command::new(EXE)
+		.args(["-l", "--verbose"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "1\n");
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stderr.contains("  vendor/ (1 skipped)"));
+	assert!(stderr.contains("  target/ (1 skipped)"));
+
+	let result = Command::new(EXE)
+		.args([
+			"-l",
+			"--no-default-ignore",
+			"go",
+			"--default-ignore",
+			"dep/",
+		])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "1\n");
+
+	let result = Command::new(EXE)
+		.args(["-l", "--no-default-ignore", "go,rust"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "5\n");
+
+	// A path given directly is scanned even if it would be ignored by default, and explain
+	// agrees, as long as it's asked about a scan of the same path
+	let exe = env::current_dir().unwrap().join(EXE);
+	let run = |args: &[&str]| {
+		let result = Command::new(&exe)
+			.args(args)
+			.current_dir(&dir)
+			.output()
+			.unwrap();
+		assert!(result.status.success());
+		String::from_utf8_lossy(&result.stdout).to_string()
+	};
+	assert_eq!(run(&["-l", "target"]), "1\n");
+	assert!(run(&["explain", "--root", "target", "target/build.rs"]).contains("  counted\n"));
+	assert_eq!(run(&["-l"]), "1\n");
+	assert!(run(&["explain", "target/build.rs"])
+		.contains("target matches \"target/\", which is ignored by default"));
+	fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn scan_with_limits() {
+	setup::before();
+
+	let dir = env::temp_dir().join(format!("kc-limits-{}", process::id()));
+	fs::create_dir_all(dir.join("a/b")).unwrap();
+	fs::create_dir_all(dir.join("real")).unwrap();
+	fs::write(dir.join("a/a.rs"), "fn a() {}\n").unwrap();
+	fs::write(dir.join("a/b/b.rs"), "fn b() {}\n").unwrap();
+	fs::write(
+		dir.join("outside.rs"),
+		"fn main() {\n\tprintln!(\"outside\");\n}\n",
+	)
+	.unwrap();
+	fs::write(dir.join("real/real.rs"), "fn real() {}\n").unwrap();
+	std::os::unix::fs::symlink("../real", dir.join("a/linked")).unwrap();
+	std::os::unix::fs::symlink("..", dir.join("real/up")).unwrap();
+
+	let count = |args: &[&str]| {
+		let result = Command::new(EXE)
+			.arg("-l")
+			.args(args)
+			.arg(&dir)
+			.output()
+			.unwrap();
+		assert!(result.status.success());
+		(
+			String::from_utf8_lossy(&result.stdout).to_string(),
+			String::from_utf8_lossy(&result.stderr).to_string(),
+		)
+	};
+
+	let (stdout, _) = count(&["--max-depth", "1"]);
+	assert_eq!(stdout, "3\n");
+
+	let (stdout, stderr) = count(&["--max-depth", "2"]);
+	assert_eq!(stdout, "5\n");
+	assert!(stderr.contains("note: skipped files or directories deeper than --max-depth (1)"));
+
+	let (stdout, stderr) = count(&["--max-filesize", "20"]);
+	assert_eq!(stdout, "3\n");
+	assert!(stderr.contains("note: skipped files larger than --max-filesize (1)"));
+	let result = Command::new(EXE)
+		.args(["explain", "--max-filesize", "20", "--root"])
+		.arg(&dir)
+		.arg(dir.join("outside.rs"))
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	let stdout = String::from_utf8_lossy(&result.stdout);
+	assert!(stdout.contains("outside.rs is larger than --max-filesize (20 bytes)"));
+	// Files given directly are still held to it, even though the walker doesn't check them
+	let result = Command::new(EXE)
+		.args(["-l", "--max-filesize", "20"])
+		.arg(dir.join("outside.rs"))
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "0\n");
+
+	// Following links finds the same files again through `a/linked`, but they're only
+	// counted once, and the link back up to the top is noticed instead of followed forever
+	let (stdout, stderr) = count(&["--follow-symlinks"]);
+	fs::remove_dir_all(&dir).unwrap();
+	assert_eq!(stdout, "6\n");
+	assert!(stderr.contains("note: skipped symbolic links which loop back to a parent directory"));
+}
+
+#[test]
+#[cfg(unix)]
+fn scan_with_unreadable_files() {
+	setup::before();
+
+	let dir = env::temp_dir().join(format!("kc-unreadable-{}", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+	fs::write(dir.join("latin1.rs"), b"// caf\xe9\n").unwrap();
+
+	// A file which isn't valid UTF-8 is still counted, and isn't a reason to fail
+	let result = Command::new(EXE)
+		.args(["-l", "--strict"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "2\n");
+	assert_eq!(String::from_utf8_lossy(&result.stderr), "");
+
+	std::os::unix::fs::symlink(dir.join("missing.rs"), dir.join("link.rs")).unwrap();
+
+	let result = Command::new(EXE).arg("-l").arg(&dir).output().unwrap();
+	assert!(result.status.success());
+	assert_eq!(String::from_utf8_lossy(&result.stdout), "2\n");
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(stderr.contains("warning: 1 file or directory could not be read (use --verbose"));
+
+	let result = Command::new(EXE)
+		.args(["-l", "--verbose", "--strict"])
+		.arg(&dir)
+		.output()
+		.unwrap();
+	fs::remove_dir_all(&dir).unwrap();
+	assert!(!result.status.success());
+	let stderr = String::from_utf8_lossy(&result.stderr);
+	assert!(!stderr.contains("latin1.rs"));
+	assert!(stderr.contains("link.rs: broken symbolic link to"));
+}
@@ -6,7 +6,7 @@ static BUILD: Once = Once::new();
 pub fn before() {
 	BUILD.call_once(|| {
 		Command::new("cargo")
-			.args(&["build", "--release"])
+			.args(["build", "--release"])
 			.status()
 			.expect("failed to build test binary");
 	});
@@ -0,0 +1,3 @@
+export function clamp(n, lo, hi) {
+	return Math.min(Math.max(n, lo), hi);
+}
@@ -0,0 +1,3 @@
+import { greet } from "./app";
+
+test("greets", () => expect(greet("kc")).toBe("hello, kc"));
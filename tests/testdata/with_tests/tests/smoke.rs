@@ -0,0 +1,4 @@
+#[test]
+fn smoke() {
+	assert_eq!(with_tests::add(2, 2), 4);
+}